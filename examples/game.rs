@@ -14,22 +14,18 @@
 //! [`agb`] provides a global allocator, allowing us to use items from the [`alloc`] crate.
 extern crate alloc;
 
-use agb::{
-    display::{object::SpriteLoader, palette16::Palette16},
-    sound::dmg::EnvelopeSettings,
-};
+use agb::display::{object::SpriteLoader, palette16::Palette16};
 use bevy::{
     app::PanicHandlerPlugin,
     diagnostic::{DiagnosticsPlugin, FrameCountPlugin},
-    input::{
-        InputSystem,
-        gamepad::{gamepad_connection_system, gamepad_event_processing_system},
-    },
     prelude::*,
     state::app::StatesPlugin,
     time::TimePlugin,
 };
-use bevy_mod_gba::{AgbSoundPlugin, Sprite, SpriteHandles, Video, prelude::*};
+use bevy_mod_gba::{
+    AgbSoundPlugin, DmgDirection, DmgEnvelope, DmgSfx, PlayDmgSound, Sprite, SpriteHandles, Video,
+    prelude::*,
+};
 use log::info;
 
 /// Main entry point.
@@ -50,7 +46,8 @@ pub extern "C" fn main() -> ! {
 
     // Next we can add any Bevy plugins we like.
     // TODO: Used `DefaultPlugins` instead of this explicit list.
-    // `DefaultPlugins` includes `InputPlugin` which is problematic on the GameBoy Advance. See below.
+    // `DefaultPlugins` includes `InputPlugin`, whose type registration causes an OOM error on the
+    // GameBoy Advance. The `AgbPlugin` already sets up gamepad input, so it isn't needed.
     app.add_plugins((
         PanicHandlerPlugin,
         TaskPoolPlugin::default(),
@@ -61,17 +58,6 @@ pub extern "C" fn main() -> ! {
         StatesPlugin,
     ));
 
-    // TODO: Type registration information from `InputPlugin` causes an OOM error.
-    // So we manually register the parts of this plugin that we need and ignore the rest.
-    app.add_systems(
-        PreUpdate,
-        (
-            gamepad_connection_system,
-            gamepad_event_processing_system.after(gamepad_connection_system),
-        )
-            .in_set(InputSystem),
-    );
-
     // Unfortunately, we currently don't have a first-party abstraction for assets or rendering.
     // This means getting assets, and rendering them must be done somewhat manually.
     app.init_non_send_resource::<Option<Sprites>>()
@@ -189,48 +175,44 @@ fn flip_player_sprite(mut player: Single<&mut Sprite, With<Player>>, gamepad: Si
     }
 }
 
+/// Played while the player is walking.
+const STEP: DmgSfx = DmgSfx::noise(4, false, 4)
+    .with_envelope(DmgEnvelope::new(1, DmgDirection::Decrease, 1))
+    .with_length(1);
+
+/// Played when the player jumps.
+/// Uses a higher priority so footsteps can't cut it off.
+const JUMP: PlayDmgSound = PlayDmgSound::new(
+    DmgSfx::noise(0, false, 0)
+        .with_envelope(DmgEnvelope::new(1, DmgDirection::Decrease, 4))
+        .with_length(32),
+)
+.with_priority(1);
+
 fn control_player(
     player: Single<(&mut Velocity, &mut Jumps), With<Player>>,
     gamepad: Single<&Gamepad>,
-    noise: ResMut<Noise>,
+    mut sounds: EventWriter<PlayDmgSound>,
 ) {
     let (mut velocity, mut jumps) = player.into_inner();
 
     if gamepad.pressed(GamepadButton::DPadLeft) {
         velocity.x -= 1.;
 
-        noise.play_sound(
-            Some(1),
-            &EnvelopeSettings::new(1, agb::sound::dmg::SoundDirection::Decrease, 1),
-            4,
-            false,
-            4,
-        );
+        sounds.write(PlayDmgSound::new(STEP));
     }
 
     if gamepad.pressed(GamepadButton::DPadRight) {
         velocity.x += 1.;
 
-        noise.play_sound(
-            Some(1),
-            &EnvelopeSettings::new(1, agb::sound::dmg::SoundDirection::Decrease, 1),
-            4,
-            false,
-            4,
-        );
+        sounds.write(PlayDmgSound::new(STEP));
     }
 
     if gamepad.just_pressed(GamepadButton::South) && jumps.current < jumps.max {
         jumps.current += 1;
         velocity.y = -5.;
 
-        noise.play_sound(
-            Some(32),
-            &EnvelopeSettings::new(1, agb::sound::dmg::SoundDirection::Decrease, 4),
-            0,
-            false,
-            0,
-        );
+        sounds.write(JUMP);
     }
 
     velocity.x = velocity.x.clamp(-2., 2.);
@@ -1,6 +1,31 @@
 use core::ops::{Deref, DerefMut};
 
-use bevy::prelude::*;
+use bevy::{
+    diagnostic::{Diagnostic, RegisterDiagnostic},
+    prelude::*,
+};
+
+mod mixer;
+mod music;
+mod pause;
+mod player;
+mod pool;
+mod sequencer;
+mod settings;
+mod sfx;
+mod spatial;
+mod wave;
+
+pub use mixer::*;
+pub use music::*;
+pub use pause::*;
+pub use player::*;
+pub use pool::*;
+pub use sequencer::*;
+pub use settings::*;
+pub use sfx::*;
+pub use spatial::*;
+pub use wave::*;
 
 /// Sets up the sound sub-system.
 #[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
@@ -9,10 +34,45 @@ pub struct AgbSoundPlugin {
     /// Otherwise, you must [enable](agb::sound::dmg::Sound::enable) it yourself
     /// using the [`Sound`] resource.
     pub enable_dmg: bool,
+    /// If set, the plugin will create and enable a direct sound [`Mixer`] running at the provided
+    /// frequency, inserted as a [non-send resource](NonSend) and serviced every frame.
+    /// Otherwise, the [`MixerController`] is made available to create one yourself.
+    pub mixer: Option<agb::sound::mixer::Frequency>,
 }
 
 impl Plugin for AgbSoundPlugin {
-    fn build(&self, _app: &mut App) {}
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DmgChannels>()
+            .init_resource::<DmgSequencer>()
+            .init_resource::<VoicePool>()
+            .init_resource::<Music>()
+            .init_resource::<AudioSettings>()
+            .init_resource::<AudioPause>()
+            .add_event::<PlayDmgSound>()
+            .add_event::<MusicEvent>()
+            .register_diagnostic(Diagnostic::new(VoicePool::DROPPED_SOUNDS))
+            .register_diagnostic(Diagnostic::new(VoicePool::STOLEN_VOICES))
+            .add_systems(
+                PostUpdate,
+                (
+                    update_audio_pause,
+                    (
+                        (tick_dmg_sequencer, play_dmg_sounds).chain(),
+                        apply_dmg_volume,
+                        (update_ducking, play_music).chain(),
+                        (
+                            apply_sound_emitters,
+                            update_audio_sinks,
+                            play_audio_players,
+                            measure_voice_pool,
+                        )
+                            .chain()
+                            .after(TransformSystem::TransformPropagate),
+                    ),
+                )
+                    .chain(),
+            );
+    }
 
     fn finish(&self, app: &mut App) {
         let Some(sound) = app
@@ -35,12 +95,20 @@ impl Plugin for AgbSoundPlugin {
 
         let channel1 = Channel::<1>::from_sound(&sound);
         let channel2 = Channel::<2>::from_sound(&sound);
+        let channel3 = Channel::<3>::from_sound(&sound);
         let noise = Noise(sound.noise());
 
-        app.insert_resource(MixerController(mixer))
-            .insert_resource(Sound(sound))
+        if let Some(frequency) = self.mixer {
+            let controller = Box::leak(Box::new(mixer));
+            app.insert_non_send_resource(Mixer::new(controller, frequency));
+        } else {
+            app.insert_resource(MixerController(mixer));
+        }
+
+        app.insert_resource(Sound(sound))
             .insert_resource(channel1)
             .insert_resource(channel2)
+            .insert_resource(channel3)
             .insert_resource(noise);
     }
 }
@@ -64,6 +132,7 @@ pub struct Noise(agb::sound::dmg::Noise);
 pub struct Channel<const N: usize> {
     c1: agb::sound::dmg::Channel1,
     c2: agb::sound::dmg::Channel2,
+    c3: WaveChannel,
 }
 
 impl Deref for Channel<1> {
@@ -94,11 +163,26 @@ impl DerefMut for Channel<2> {
     }
 }
 
+impl Deref for Channel<3> {
+    type Target = WaveChannel;
+
+    fn deref(&self) -> &Self::Target {
+        &self.c3
+    }
+}
+
+impl DerefMut for Channel<3> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.c3
+    }
+}
+
 impl Channel<1> {
     fn from_sound(sound: &agb::sound::dmg::Sound) -> Self {
         Self {
             c1: sound.channel1(),
             c2: sound.channel2(),
+            c3: WaveChannel::new(),
         }
     }
 }
@@ -108,6 +192,17 @@ impl Channel<2> {
         Self {
             c1: sound.channel1(),
             c2: sound.channel2(),
+            c3: WaveChannel::new(),
+        }
+    }
+}
+
+impl Channel<3> {
+    fn from_sound(sound: &agb::sound::dmg::Sound) -> Self {
+        Self {
+            c1: sound.channel1(),
+            c2: sound.channel2(),
+            c3: WaveChannel::new(),
         }
     }
 }
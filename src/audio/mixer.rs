@@ -0,0 +1,69 @@
+use core::ops::{Deref, DerefMut};
+
+use agb::sound::mixer::{ChannelId, SoundChannel};
+
+/// The direct sound [mixer](agb::sound::mixer::Mixer), available as a
+/// [non-send resource](bevy::prelude::NonSend) when
+/// [`AgbSoundPlugin::mixer`](super::AgbSoundPlugin::mixer) is set.
+///
+/// The mixer is enabled by the [`AgbSoundPlugin`](super::AgbSoundPlugin), and its buffers are
+/// refilled by the [`AgbRunnerPlugin`](crate::AgbRunnerPlugin) after every V-Blank.
+pub struct Mixer(agb::sound::mixer::Mixer<'static>);
+
+impl Mixer {
+    pub(crate) fn new(
+        controller: &'static mut agb::sound::mixer::MixerController,
+        frequency: agb::sound::mixer::Frequency,
+    ) -> Self {
+        let mut mixer = controller.mixer(frequency);
+        mixer.enable();
+        Self(mixer)
+    }
+
+    /// Starts playing a sound, returning an identifier which can be used to control it.
+    /// Returns [`None`] if there are no free channels.
+    /// See [`play_sound`](agb::sound::mixer::Mixer::play_sound).
+    pub fn play(&mut self, sound: SoundChannel) -> Option<ChannelId> {
+        self.0.play_sound(sound)
+    }
+
+    /// Returns `true` if the sound identified by `id` is still playing or paused.
+    pub fn is_playing(&mut self, id: &ChannelId) -> bool {
+        self.0.channel(id).is_some()
+    }
+
+    /// Stops the sound identified by `id`, freeing its channel.
+    pub fn stop(&mut self, id: &ChannelId) {
+        if let Some(channel) = self.0.channel(id) {
+            channel.stop();
+        }
+    }
+
+    /// Pauses the sound identified by `id`, keeping its position.
+    pub fn pause(&mut self, id: &ChannelId) {
+        if let Some(channel) = self.0.channel(id) {
+            channel.pause();
+        }
+    }
+
+    /// Resumes the sound identified by `id` after it was [paused](Self::pause).
+    pub fn resume(&mut self, id: &ChannelId) {
+        if let Some(channel) = self.0.channel(id) {
+            channel.resume();
+        }
+    }
+}
+
+impl Deref for Mixer {
+    type Target = agb::sound::mixer::Mixer<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Mixer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
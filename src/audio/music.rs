@@ -0,0 +1,687 @@
+use alloc::{borrow::Cow, vec::Vec};
+
+use agb::{
+    fixnum::Num,
+    sound::mixer::{ChannelId, SoundChannel},
+};
+use agb_tracker::{Track, TrackerInner};
+use agb_tracker_interop::{Jump, PatternEffect};
+use bevy::prelude::*;
+use log::warn;
+
+use super::{AudioPause, AudioSettings, Mixer};
+
+/// Plays tracker music (XM, S3M, and MOD modules) through the direct sound [`Mixer`].
+///
+/// Tracks are included with the macros provided by [`agb_tracker`], such as
+/// [`include_xm`](agb_tracker::include_xm):
+///
+/// ```ignore
+/// static THEME: Track = include_xm!("music/theme.xm");
+///
+/// fn start_music(mut music: ResMut<Music>) {
+///     music.crossfade(&THEME, 60);
+/// }
+/// ```
+///
+/// The music is stepped once per frame, sending [`MusicEvent`]s as patterns and the song end.
+/// Changes take effect the next time the music is stepped.
+///
+/// Tracker music requires the [`Mixer`] to run at
+/// [`Frequency::Hz32768`](agb::sound::mixer::Frequency::Hz32768).
+#[derive(Resource)]
+pub struct Music {
+    current: Option<Song>,
+    outgoing: Vec<Song>,
+    volume: Num<i16, 8>,
+    tempo: Num<u32, 8>,
+    looping: MusicLoop,
+}
+
+impl Default for Music {
+    fn default() -> Self {
+        Self {
+            current: None,
+            outgoing: Vec::new(),
+            volume: Num::new(1),
+            tempo: Num::new(1),
+            looping: MusicLoop::Forever,
+        }
+    }
+}
+
+impl Music {
+    /// Starts playing `track` from the beginning, immediately stopping any other music.
+    pub fn play(&mut self, track: &'static Track) {
+        self.crossfade(track, 0);
+    }
+
+    /// Starts playing `track` from the beginning, fading it in over `frames` frames while the
+    /// current music fades out.
+    pub fn crossfade(&mut self, track: &'static Track, frames: u32) {
+        self.fade_out(frames);
+        self.current = Some(Song::new(track, self.looping, frames));
+    }
+
+    /// Immediately stops the music.
+    pub fn stop(&mut self) {
+        self.fade_out(0);
+    }
+
+    /// Fades the music out over `frames` frames, then stops it.
+    pub fn fade_out(&mut self, frames: u32) {
+        if let Some(mut song) = self.current.take() {
+            song.fade_to(Num::new(0), frames);
+            self.outgoing.push(song);
+        }
+    }
+
+    /// Returns `true` if a track is playing, ignoring tracks which are fading out.
+    pub fn is_playing(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// The track currently playing, if any.
+    pub fn track(&self) -> Option<&'static Track> {
+        self.current.as_ref().map(|song| song.track)
+    }
+
+    /// Number of [`Mixer`] channels used by music, including tracks which are fading out.
+    pub fn voices(&self) -> usize {
+        self.current
+            .iter()
+            .chain(&self.outgoing)
+            .map(|song| song.voices.len())
+            .sum()
+    }
+
+    /// The current playback position within the current track, if any.
+    pub fn position(&self) -> Option<MusicPosition> {
+        self.current
+            .as_ref()
+            .map(|song| song.sequencer.position(song.track))
+    }
+
+    /// The volume of all music, where 1 is the volume the tracks were authored at.
+    pub fn volume(&self) -> Num<i16, 8> {
+        self.volume
+    }
+
+    /// Sets the [volume](Self::volume) of all music.
+    pub fn set_volume(&mut self, volume: Num<i16, 8>) {
+        self.volume = volume;
+    }
+
+    /// The speed music is played at, where 1 is the tempo the tracks were authored at.
+    /// Only affects the timing of notes, not their pitch.
+    pub fn tempo(&self) -> Num<u32, 8> {
+        self.tempo
+    }
+
+    /// Sets the [tempo](Self::tempo) of all music.
+    pub fn set_tempo(&mut self, tempo: Num<u32, 8>) {
+        self.tempo = tempo;
+    }
+
+    /// How many times tracks play through.
+    pub fn looping(&self) -> MusicLoop {
+        self.looping
+    }
+
+    /// Sets [how many times](Self::looping) tracks play through, including the current track.
+    /// The loop count of the current track is reset.
+    pub fn set_looping(&mut self, looping: MusicLoop) {
+        self.looping = looping;
+
+        if let Some(song) = &mut self.current {
+            song.remaining = looping.count();
+        }
+    }
+}
+
+/// How many times a track plays through before [`Music`] stops.
+///
+/// Each time a track reaches its end, playback continues from the restart position stored in the
+/// module.
+/// The restart position can't be changed at runtime, as [`agb_tracker`] always follows the
+/// module, so a different loop point must be set when authoring the track.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum MusicLoop {
+    /// Loop forever.
+    #[default]
+    Forever,
+    /// Play the track once, then stop.
+    Once,
+    /// Play the track the provided number of times, then stop.
+    Times(u32),
+}
+
+impl MusicLoop {
+    const fn count(self) -> Option<u32> {
+        match self {
+            Self::Forever => None,
+            Self::Once => Some(1),
+            Self::Times(times) => Some(times),
+        }
+    }
+}
+
+/// A position within a [`Track`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct MusicPosition {
+    /// Index into the order list of the track.
+    pub order: usize,
+    /// The pattern played at this point in the order list.
+    pub pattern: usize,
+    /// The row within the pattern.
+    pub row: usize,
+}
+
+/// Sent as the current [`Music`] moves through its track.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MusicEvent {
+    /// A pattern finished playing.
+    PatternEnded {
+        /// Index into the order list of the pattern which finished.
+        order: usize,
+        /// The pattern which finished.
+        pattern: usize,
+    },
+    /// The track reached its end, either looping back or stopping depending on its
+    /// [`MusicLoop`].
+    SongEnded {
+        /// `true` if the track is continuing from its restart position.
+        looping: bool,
+    },
+}
+
+struct Song {
+    track: &'static Track,
+    tracker: TrackerInner<'static, VoiceId>,
+    sequencer: Sequencer,
+    voices: Voices,
+    remaining: Option<u32>,
+    gain: Num<i16, 8>,
+    target: Num<i16, 8>,
+    fade_step: Num<i16, 8>,
+    steps: Num<u32, 8>,
+}
+
+impl Song {
+    fn new(track: &'static Track, looping: MusicLoop, fade_in: u32) -> Self {
+        let mut song = Self {
+            track,
+            tracker: TrackerInner::new(track),
+            sequencer: Sequencer::new(track),
+            voices: Voices::default(),
+            remaining: looping.count(),
+            gain: Num::new(0),
+            target: Num::new(0),
+            fade_step: Num::new(0),
+            steps: Num::new(0),
+        };
+
+        song.fade_to(Num::new(1), fade_in);
+        song
+    }
+
+    fn fade_to(&mut self, target: Num<i16, 8>, frames: u32) {
+        self.target = target;
+
+        let distance = (target - self.gain).abs();
+
+        self.fade_step = if frames == 0 {
+            distance
+        } else {
+            let frames = i16::try_from(frames).unwrap_or(i16::MAX);
+            (distance / frames).max(Num::from_raw(1))
+        };
+    }
+
+    fn update_gain(&mut self) {
+        if self.gain < self.target {
+            self.gain = (self.gain + self.fade_step).min(self.target);
+        } else {
+            self.gain = (self.gain - self.fade_step).max(self.target);
+        }
+    }
+
+    /// Advances the song by one frame, returning `false` once it has finished.
+    fn step(
+        &mut self,
+        mixer: &mut agb::sound::mixer::Mixer<'static>,
+        tempo: Num<u32, 8>,
+        volume: Num<i16, 8>,
+        mut events: Option<&mut EventWriter<MusicEvent>>,
+    ) -> bool {
+        self.update_gain();
+        self.steps += tempo;
+
+        while self.steps >= Num::new(1) {
+            self.steps -= Num::new(1);
+
+            let transition = self.sequencer.advance(self.track);
+
+            if let Some(Transition {
+                order,
+                pattern,
+                end,
+            }) = transition
+            {
+                let looping = end && self.remaining.is_none_or(|remaining| remaining > 1);
+
+                if let Some(events) = events.as_deref_mut() {
+                    events.write(MusicEvent::PatternEnded { order, pattern });
+
+                    if end {
+                        events.write(MusicEvent::SongEnded { looping });
+                    }
+                }
+
+                if end && !looping {
+                    self.voices.stop(mixer);
+                    return false;
+                }
+
+                if end {
+                    self.remaining = self.remaining.map(|remaining| remaining - 1);
+                }
+            }
+
+            self.tracker.step(&mut VoiceMixer {
+                mixer: &mut *mixer,
+                voices: &mut self.voices,
+            });
+        }
+
+        self.voices.sync(mixer, volume * self.gain, false);
+
+        true
+    }
+}
+
+/// Mirrors the sequencing logic of the tracker, which doesn't expose its position.
+struct Sequencer {
+    first: bool,
+    frame: Num<u32, 8>,
+    tick: u32,
+    frames_per_tick: Num<u32, 8>,
+    ticks_per_step: u32,
+    order: usize,
+    row: usize,
+    jump: Option<Jump>,
+}
+
+struct Transition {
+    order: usize,
+    pattern: usize,
+    end: bool,
+}
+
+impl Sequencer {
+    fn new(track: &Track) -> Self {
+        Self {
+            first: true,
+            frame: Num::new(0),
+            tick: 0,
+            frames_per_tick: track.frames_per_tick,
+            ticks_per_step: track.ticks_per_step,
+            order: 0,
+            row: 0,
+            jump: None,
+        }
+    }
+
+    fn position(&self, track: &Track) -> MusicPosition {
+        MusicPosition {
+            order: self.order,
+            pattern: track.patterns_to_play[self.order],
+            row: self.row,
+        }
+    }
+
+    fn pattern_length(track: &Track, order: usize) -> usize {
+        track.patterns[track.patterns_to_play[order]].length
+    }
+
+    /// Advances one frame, returning the transition if a pattern finished.
+    fn advance(&mut self, track: &Track) -> Option<Transition> {
+        if self.first {
+            self.first = false;
+            self.apply_row(track);
+            return None;
+        }
+
+        self.frame += 1;
+
+        if self.frame < self.frames_per_tick {
+            return None;
+        }
+
+        self.frame -= self.frames_per_tick;
+        self.tick += 1;
+
+        let mut transition = None;
+
+        if self.tick >= self.ticks_per_step {
+            self.tick = 0;
+
+            let previous = self.order;
+
+            let (order, row) = match self.jump.take() {
+                Some(Jump::Position { pattern }) => (usize::from(pattern), 0),
+                Some(Jump::PatternBreak { row }) => (self.order + 1, usize::from(row)),
+                Some(Jump::Combined { pattern, row }) => (usize::from(pattern), usize::from(row)),
+                None if self.row + 1 >= Self::pattern_length(track, self.order) => {
+                    (self.order + 1, 0)
+                }
+                None => (self.order, self.row + 1),
+            };
+
+            let order = if order >= track.patterns_to_play.len() {
+                track.repeat
+            } else {
+                order
+            };
+
+            let row = if row >= Self::pattern_length(track, order) {
+                0
+            } else {
+                row
+            };
+
+            if order != previous || row <= self.row {
+                transition = Some(Transition {
+                    order: previous,
+                    pattern: track.patterns_to_play[previous],
+                    end: order <= previous,
+                });
+            }
+
+            self.order = order;
+            self.row = row;
+        }
+
+        self.apply_row(track);
+
+        transition
+    }
+
+    fn apply_row(&mut self, track: &Track) {
+        let pattern = &track.patterns[track.patterns_to_play[self.order]];
+        let start = pattern.start_position + self.row * track.num_channels;
+
+        for slot in &track.pattern_data[start..start + track.num_channels] {
+            for effect in [&slot.effect1, &slot.effect2] {
+                match effect {
+                    PatternEffect::SetTicksPerStep(ticks) => self.ticks_per_step = *ticks,
+                    PatternEffect::SetFramesPerTick(frames) => self.frames_per_tick = *frames,
+                    PatternEffect::Jump(jump) => self.jump = Some(jump.clone()),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// A note requested by the tracker, applied to a [`Mixer`] channel after each step so the
+/// music volume can be applied on top.
+struct Voice {
+    data: &'static [u8],
+    channel: Option<ChannelId>,
+    volume: Num<i16, 8>,
+    panning: Num<i16, 8>,
+    playback: Num<u32, 8>,
+    restart_point: Num<u32, 8>,
+    position: Option<Num<u32, 8>>,
+    looping: bool,
+    paused: bool,
+    stopped: bool,
+}
+
+impl agb_tracker::SoundChannel for Voice {
+    fn new(data: &Cow<'static, [u8]>) -> Self {
+        // Samples are played directly from the ROM, so they can't be borrowed from anywhere else.
+        let (data, stopped) = match data {
+            Cow::Borrowed(data) => (*data, false),
+            Cow::Owned(_) => {
+                warn!("Tracker samples must be included as static data, skipping sample");
+                (&[][..], true)
+            }
+        };
+
+        Self {
+            data,
+            channel: None,
+            volume: Num::new(1),
+            panning: Num::new(0),
+            playback: Num::new(1),
+            restart_point: Num::new(0),
+            position: None,
+            looping: false,
+            paused: false,
+            stopped,
+        }
+    }
+
+    fn stop(&mut self) {
+        self.stopped = true;
+    }
+
+    fn pause(&mut self) -> &mut Self {
+        self.paused = true;
+        self
+    }
+
+    fn resume(&mut self) -> &mut Self {
+        self.paused = false;
+        self
+    }
+
+    fn should_loop(&mut self) -> &mut Self {
+        self.looping = true;
+        self
+    }
+
+    fn volume(&mut self, value: impl Into<Num<i16, 8>>) -> &mut Self {
+        self.volume = value.into();
+        self
+    }
+
+    fn restart_point(&mut self, value: impl Into<Num<u32, 8>>) -> &mut Self {
+        self.restart_point = value.into();
+        self
+    }
+
+    fn playback(&mut self, playback_speed: impl Into<Num<u32, 8>>) -> &mut Self {
+        self.playback = playback_speed.into();
+        self
+    }
+
+    fn panning(&mut self, panning: impl Into<Num<i16, 8>>) -> &mut Self {
+        self.panning = panning.into();
+        self
+    }
+
+    fn set_pos(&mut self, pos: impl Into<Num<u32, 8>>) -> &mut Self {
+        self.position = Some(pos.into());
+        self
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct VoiceId {
+    index: usize,
+    generation: u32,
+}
+
+#[derive(Default)]
+struct Voices {
+    slots: Vec<(u32, Option<Voice>)>,
+    generation: u32,
+}
+
+impl Voices {
+    /// Number of voices which are playing.
+    fn len(&self) -> usize {
+        self.slots.iter().filter(|(_, slot)| slot.is_some()).count()
+    }
+
+    /// Applies each voice to its channel, holding every channel in place if `paused` is set.
+    fn sync(
+        &mut self,
+        mixer: &mut agb::sound::mixer::Mixer<'static>,
+        gain: Num<i16, 8>,
+        paused: bool,
+    ) {
+        for (_, slot) in &mut self.slots {
+            let Some(voice) = slot else {
+                continue;
+            };
+
+            let Some(channel) = voice.channel.as_ref().and_then(|id| mixer.channel(id)) else {
+                *slot = None;
+                continue;
+            };
+
+            if voice.stopped {
+                channel.stop();
+                *slot = None;
+                continue;
+            }
+
+            channel
+                .volume(voice.volume * gain)
+                .panning(voice.panning)
+                .playback(voice.playback);
+
+            if let Some(position) = voice.position.take() {
+                channel.set_pos(position);
+            }
+
+            if voice.paused || paused {
+                channel.pause();
+            } else {
+                channel.resume();
+            }
+        }
+    }
+
+    fn stop(&mut self, mixer: &mut agb::sound::mixer::Mixer<'static>) {
+        for (_, slot) in &mut self.slots {
+            if let Some(channel) = slot
+                .take()
+                .and_then(|voice| voice.channel)
+                .and_then(|id| mixer.channel(&id))
+            {
+                channel.stop();
+            }
+        }
+    }
+}
+
+/// Lets the tracker drive [`Voice`]s as if they were mixer channels.
+struct VoiceMixer<'a> {
+    mixer: &'a mut agb::sound::mixer::Mixer<'static>,
+    voices: &'a mut Voices,
+}
+
+impl agb_tracker::Mixer for VoiceMixer<'_> {
+    type ChannelId = VoiceId;
+    type SoundChannel = Voice;
+
+    fn channel(&mut self, channel_id: &VoiceId) -> Option<&mut Voice> {
+        let (generation, slot) = self.voices.slots.get_mut(channel_id.index)?;
+
+        if *generation != channel_id.generation {
+            return None;
+        }
+
+        let playing = slot.as_ref().is_some_and(|voice| {
+            voice
+                .channel
+                .as_ref()
+                .is_some_and(|id| self.mixer.channel(id).is_some())
+        });
+
+        if !playing {
+            *slot = None;
+        }
+
+        slot.as_mut()
+    }
+
+    fn play_sound(&mut self, mut voice: Voice) -> Option<VoiceId> {
+        if voice.stopped {
+            return None;
+        }
+
+        let mut channel = SoundChannel::new(voice.data);
+
+        if voice.looping {
+            channel.should_loop().restart_point(voice.restart_point);
+        }
+
+        voice.channel = Some(self.mixer.play_sound(channel)?);
+
+        self.voices.generation = self.voices.generation.wrapping_add(1);
+        let generation = self.voices.generation;
+
+        let index = match self
+            .voices
+            .slots
+            .iter()
+            .position(|(_, slot)| slot.is_none())
+        {
+            Some(index) => {
+                self.voices.slots[index] = (generation, Some(voice));
+                index
+            }
+            None => {
+                self.voices.slots.push((generation, Some(voice)));
+                self.voices.slots.len() - 1
+            }
+        };
+
+        Some(VoiceId { index, generation })
+    }
+}
+
+pub(crate) fn play_music(
+    mut music: ResMut<Music>,
+    settings: Res<AudioSettings>,
+    pause: Res<AudioPause>,
+    mixer: Option<NonSendMut<Mixer>>,
+    mut events: EventWriter<MusicEvent>,
+) {
+    let Some(mut mixer) = mixer else {
+        return;
+    };
+
+    let music = &mut *music;
+    let mixer = &mut **mixer;
+    let volume = music.volume * settings.music_volume();
+
+    if pause.is_paused() {
+        for song in music.outgoing.iter_mut().chain(&mut music.current) {
+            song.voices.sync(mixer, volume * song.gain, true);
+        }
+
+        return;
+    }
+
+    music.outgoing.retain_mut(|song| {
+        if song.gain == Num::new(0) {
+            song.voices.stop(mixer);
+            return false;
+        }
+
+        song.step(mixer, music.tempo, volume, None)
+    });
+
+    if let Some(song) = &mut music.current {
+        if !song.step(mixer, music.tempo, volume, Some(&mut events)) {
+            music.current = None;
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+
+/// Pauses all audio, resuming every sound exactly where it left off.
+///
+/// While paused, [`AudioPlayer`](super::AudioPlayer) sounds and [`Music`](super::Music) are held
+/// in place, the [`DmgSequencer`](super::DmgSequencer) stops advancing, and the DMG channels are
+/// silenced.
+///
+/// To pause audio while in a particular state, add the [`AgbAudioPausePlugin`]:
+///
+/// ```ignore
+/// app.add_plugins(AgbAudioPausePlugin::in_state(GameState::Paused));
+/// ```
+///
+/// Otherwise, use [`pause_audio`] and [`resume_audio`] from any schedule.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AudioPause {
+    /// If `true`, audio is paused.
+    pub paused: bool,
+    /// If `true`, audio is also paused while [`Time<Virtual>`] is paused.
+    pub with_virtual_time: bool,
+    in_state: bool,
+    active: bool,
+}
+
+impl Default for AudioPause {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            with_virtual_time: true,
+            in_state: false,
+            active: false,
+        }
+    }
+}
+
+impl AudioPause {
+    /// Returns `true` if audio is currently paused, either [directly](Self::paused), by
+    /// [`Time<Virtual>`], or by the state followed by an [`AgbAudioPausePlugin`].
+    pub fn is_paused(&self) -> bool {
+        self.active
+    }
+}
+
+/// Pauses all audio. See [`AudioPause`].
+pub fn pause_audio(mut pause: ResMut<AudioPause>) {
+    pause.paused = true;
+}
+
+/// Resumes all audio after it was paused with [`pause_audio`]. See [`AudioPause`].
+pub fn resume_audio(mut pause: ResMut<AudioPause>) {
+    pause.paused = false;
+}
+
+/// Pauses all audio while the game is in a particular [state](States).
+///
+/// This plugin isn't part of the [`AgbPlugin`](crate::AgbPlugin), so must be added to opt in.
+/// Audio can still be paused [directly](AudioPause::paused) in any other state.
+pub struct AgbAudioPausePlugin<S: States> {
+    /// The state in which audio is paused.
+    pub state: S,
+}
+
+impl<S: States> AgbAudioPausePlugin<S> {
+    /// Pauses audio while in `state`.
+    pub fn in_state(state: S) -> Self {
+        Self { state }
+    }
+}
+
+impl<S: States> Plugin for AgbAudioPausePlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            follow_state(self.state.clone()).before(update_audio_pause),
+        );
+    }
+}
+
+fn follow_state<S: States>(
+    paused_state: S,
+) -> impl FnMut(ResMut<AudioPause>, Option<Res<State<S>>>) {
+    move |mut pause, state| {
+        let in_state = state.is_some_and(|state| *state.get() == paused_state);
+
+        if pause.in_state != in_state {
+            pause.in_state = in_state;
+        }
+    }
+}
+
+pub(crate) fn update_audio_pause(mut pause: ResMut<AudioPause>, time: Option<Res<Time<Virtual>>>) {
+    let active = pause.paused
+        || pause.in_state
+        || (pause.with_virtual_time && time.is_some_and(|time| time.is_paused()));
+
+    if pause.active != active {
+        pause.active = active;
+    }
+}
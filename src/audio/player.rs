@@ -0,0 +1,359 @@
+use agb::{fixnum::Num, sound::mixer::SoundChannel};
+use bevy::prelude::*;
+
+use super::{
+    Allocation, AudioPause, AudioSettings, Mixer, Music, SoundEmitter, SoundListener, VoicePool,
+};
+
+/// Sound data which can be played through the direct sound [`Mixer`].
+/// Create one from the output of [`include_wav`](agb::include_wav):
+///
+/// ```ignore
+/// static JUMP: GbaAudioSource = GbaAudioSource::new(include_wav!("sfx/jump.wav"));
+/// ```
+///
+/// The sample rate of the data must match the frequency of the [`Mixer`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GbaAudioSource {
+    data: &'static [u8],
+    stereo: bool,
+}
+
+impl GbaAudioSource {
+    /// Creates a source from mono sound data.
+    pub const fn new(data: &'static [u8]) -> Self {
+        Self {
+            data,
+            stereo: false,
+        }
+    }
+
+    /// Creates a source from stereo sound data.
+    /// Stereo sounds can't be panned or have their playback speed changed.
+    pub const fn stereo(data: &'static [u8]) -> Self {
+        Self { data, stereo: true }
+    }
+
+    /// The raw sound data of this source.
+    pub const fn data(&self) -> &'static [u8] {
+        self.data
+    }
+
+    /// Returns `true` if this source contains stereo data.
+    pub const fn is_stereo(&self) -> bool {
+        self.stereo
+    }
+}
+
+/// Plays a [`GbaAudioSource`] through the direct sound [`Mixer`], configured by the entity's
+/// [`PlaybackSettings`].
+///
+/// Once playback has started, an [`AudioSink`] is inserted to control it.
+/// Removing the [`AudioSink`] stops playback and removes the [`AudioPlayer`], so the sound isn't
+/// started again, unless a new [`AudioPlayer`] was inserted at the same time to replace it.
+/// Despawning the entity also stops playback.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Deref)]
+#[require(PlaybackSettings)]
+pub struct AudioPlayer(pub GbaAudioSource);
+
+/// What happens to an [`AudioPlayer`] entity when its sound finishes.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum PlaybackMode {
+    /// Play the sound once, leaving the entity untouched.
+    #[default]
+    Once,
+    /// Repeat the sound forever.
+    Loop,
+    /// Despawn the entity once the sound finishes.
+    Despawn,
+    /// Remove the audio components from the entity once the sound finishes.
+    Remove,
+}
+
+/// Initial settings used when an [`AudioPlayer`] starts playing.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PlaybackSettings {
+    /// What to do once the sound finishes.
+    pub mode: PlaybackMode,
+    /// Volume of the sound, where 1 is the volume it was recorded at.
+    pub volume: Num<i16, 8>,
+    /// Position of the sound from -1 (left) to 1 (right).
+    pub panning: Num<i16, 8>,
+    /// Playback speed, where 1 is the speed it was recorded at.
+    pub speed: Num<u32, 8>,
+    /// Whether the sound starts paused.
+    pub paused: bool,
+    /// Priority of this sound within the [`VoicePool`].
+    /// Sounds can only take the voice of sounds with an equal or lower priority.
+    ///
+    /// Sounds with a priority above 0 are also played on a high priority [`Mixer`] channel, so
+    /// they can take a channel from [`Music`](super::Music) when every channel is busy.
+    pub priority: u8,
+    /// If set, at most this many instances of the same [`GbaAudioSource`] can play at once.
+    pub max_instances: Option<u8>,
+}
+
+impl Default for PlaybackSettings {
+    fn default() -> Self {
+        Self::ONCE
+    }
+}
+
+impl PlaybackSettings {
+    /// Play the sound once.
+    pub const ONCE: Self = Self {
+        mode: PlaybackMode::Once,
+        volume: Num::from_raw(1 << 8),
+        panning: Num::from_raw(0),
+        speed: Num::from_raw(1 << 8),
+        paused: false,
+        priority: 0,
+        max_instances: None,
+    };
+
+    /// Repeat the sound forever.
+    pub const LOOP: Self = Self::ONCE.with_mode(PlaybackMode::Loop);
+
+    /// Despawn the entity once the sound finishes.
+    pub const DESPAWN: Self = Self::ONCE.with_mode(PlaybackMode::Despawn);
+
+    /// Remove the audio components from the entity once the sound finishes.
+    pub const REMOVE: Self = Self::ONCE.with_mode(PlaybackMode::Remove);
+
+    const fn with_mode(mut self, mode: PlaybackMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the [volume](Self::volume).
+    pub const fn with_volume(mut self, volume: Num<i16, 8>) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    /// Sets the [panning](Self::panning).
+    pub const fn with_panning(mut self, panning: Num<i16, 8>) -> Self {
+        self.panning = panning;
+        self
+    }
+
+    /// Sets the [playback speed](Self::speed).
+    pub const fn with_speed(mut self, speed: Num<u32, 8>) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Starts the sound [paused](Self::paused).
+    pub const fn paused(mut self) -> Self {
+        self.paused = true;
+        self
+    }
+
+    /// Sets the [priority](Self::priority) of the sound within the [`VoicePool`].
+    pub const fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the [maximum number of instances](Self::max_instances) of the sound.
+    pub const fn with_max_instances(mut self, max_instances: u8) -> Self {
+        self.max_instances = Some(max_instances);
+        self
+    }
+}
+
+/// Controls a sound started by an [`AudioPlayer`].
+/// Changes are applied to the [`Mixer`] every frame.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AudioSink {
+    /// Volume of the sound, where 1 is the volume it was recorded at.
+    pub volume: Num<i16, 8>,
+    /// Position of the sound from -1 (left) to 1 (right).
+    pub panning: Num<i16, 8>,
+    /// Whether the sound is paused.
+    pub paused: bool,
+    finished: bool,
+}
+
+impl AudioSink {
+    /// Pauses playback.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resumes playback.
+    pub fn play(&mut self) {
+        self.paused = false;
+    }
+
+    /// Toggles between paused and playing.
+    pub fn toggle(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Returns `true` once the sound has finished playing, or was dropped or stopped by the
+    /// [`VoicePool`].
+    pub const fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+pub(crate) fn play_audio_players(
+    mut commands: Commands,
+    players: Query<
+        (
+            Entity,
+            &AudioPlayer,
+            &PlaybackSettings,
+            Option<(&SoundEmitter, &GlobalTransform)>,
+        ),
+        Without<AudioSink>,
+    >,
+    listeners: Query<&GlobalTransform, With<SoundListener>>,
+    audio_settings: Res<AudioSettings>,
+    pause: Res<AudioPause>,
+    mixer: Option<NonSendMut<Mixer>>,
+    mut pool: ResMut<VoicePool>,
+    music: Res<Music>,
+) {
+    let Some(mut mixer) = mixer else {
+        return;
+    };
+
+    let listener = listeners.single().ok();
+
+    for (entity, player, settings, emitter) in &players {
+        let (volume, panning) = match (emitter, listener) {
+            (Some((emitter, transform)), Some(listener)) => {
+                emitter.spatialize(transform, listener, settings.volume)
+            }
+            _ => (settings.volume, settings.panning),
+        };
+
+        let mut sound = if settings.priority > 0 {
+            SoundChannel::new_high_priority(player.data)
+        } else {
+            SoundChannel::new(player.data)
+        };
+
+        sound.volume(volume * audio_settings.sfx_volume());
+
+        if player.stereo {
+            sound.stereo();
+        } else {
+            sound.panning(panning).playback(settings.speed);
+        }
+
+        if settings.mode == PlaybackMode::Loop {
+            sound.should_loop();
+        }
+
+        if settings.paused || pause.is_paused() {
+            sound.pause();
+        }
+
+        let id = match pool.allocate(player, settings, music.voices()) {
+            Allocation::Free => mixer.play(sound),
+            Allocation::Steal(victim) => {
+                if let Some(id) = pool.remove(victim) {
+                    mixer.stop(&id);
+                }
+
+                pool.record_stolen();
+                mixer.play(sound)
+            }
+            Allocation::Drop => None,
+        };
+
+        let dropped = id.is_none();
+
+        if let Some(id) = id {
+            pool.insert(entity, id, player, settings, volume);
+        } else {
+            pool.record_dropped();
+        }
+
+        commands.entity(entity).insert(AudioSink {
+            volume,
+            panning,
+            paused: settings.paused,
+            finished: dropped,
+        });
+
+        if dropped {
+            finish(&mut commands, entity, settings.mode);
+        }
+    }
+}
+
+/// Applies the [`PlaybackMode`] of a sound which has finished, or couldn't be played.
+fn finish(commands: &mut Commands, entity: Entity, mode: PlaybackMode) {
+    match mode {
+        PlaybackMode::Despawn => {
+            commands.entity(entity).despawn();
+        }
+        PlaybackMode::Remove => {
+            commands
+                .entity(entity)
+                .remove::<(AudioPlayer, PlaybackSettings, AudioSink)>();
+        }
+        PlaybackMode::Once | PlaybackMode::Loop => {}
+    }
+}
+
+pub(crate) fn update_audio_sinks(
+    mut commands: Commands,
+    mut sinks: Query<(Entity, &AudioPlayer, &PlaybackSettings, &mut AudioSink)>,
+    mut removed: RemovedComponents<AudioSink>,
+    stopped: Query<Ref<AudioPlayer>, Without<AudioSink>>,
+    audio_settings: Res<AudioSettings>,
+    pause: Res<AudioPause>,
+    mixer: Option<NonSendMut<Mixer>>,
+    mut pool: ResMut<VoicePool>,
+) {
+    let Some(mut mixer) = mixer else {
+        return;
+    };
+
+    for entity in removed.read() {
+        if let Some(id) = pool.remove(entity) {
+            mixer.stop(&id);
+        }
+
+        // Otherwise, the sound would be started again from the beginning. A player inserted
+        // along with removing the sink is a new sound, so is left to play.
+        if stopped.get(entity).is_ok_and(|player| !player.is_changed()) {
+            commands.entity(entity).try_remove::<AudioPlayer>();
+        }
+    }
+
+    for (entity, player, settings, mut sink) in &mut sinks {
+        if let Some(channel) = pool.channel(entity).and_then(|id| mixer.channel(id)) {
+            if sink.is_changed() || audio_settings.is_changed() || pause.is_changed() {
+                pool.set_volume(entity, sink.volume);
+                channel.volume(sink.volume * audio_settings.sfx_volume());
+
+                if !player.stereo {
+                    channel.panning(sink.panning);
+                }
+
+                if sink.paused || pause.is_paused() {
+                    channel.pause();
+                } else {
+                    channel.resume();
+                }
+            }
+
+            continue;
+        }
+
+        if sink.finished {
+            continue;
+        }
+
+        pool.remove(entity);
+        sink.finished = true;
+
+        finish(&mut commands, entity, settings.mode);
+    }
+}
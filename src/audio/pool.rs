@@ -0,0 +1,190 @@
+use agb::{fixnum::Num, sound::mixer::ChannelId};
+use bevy::{
+    diagnostic::{DiagnosticPath, Diagnostics},
+    platform_support::collections::HashMap,
+    prelude::*,
+};
+
+use super::{GbaAudioSource, PlaybackSettings};
+
+/// Manages the [`Mixer`](super::Mixer) channels used by [`AudioPlayer`](super::AudioPlayer)s.
+///
+/// When a new sound can't be given a voice, either because the pool is full or because too many
+/// [instances](PlaybackSettings::max_instances) of it are playing, a playing sound of equal or
+/// lower [priority](PlaybackSettings::priority) is stopped according to the
+/// [stealing](Self::stealing) policy.
+/// If no sound can be stolen, the new sound is dropped and reported through the
+/// [`DROPPED_SOUNDS`](Self::DROPPED_SOUNDS) diagnostic.
+#[derive(Resource)]
+pub struct VoicePool {
+    /// Maximum number of [`Mixer`](super::Mixer) channels which can be used at once, including
+    /// those playing [`Music`](super::Music).
+    /// The [`Mixer`](super::Mixer) has 8 channels.
+    pub max_voices: usize,
+    /// How a playing sound is chosen to make room for a new one.
+    pub stealing: VoiceStealing,
+    voices: HashMap<Entity, Voice>,
+    started: u32,
+    dropped: u32,
+    stolen: u32,
+}
+
+impl Default for VoicePool {
+    fn default() -> Self {
+        Self {
+            max_voices: 8,
+            stealing: VoiceStealing::Oldest,
+            voices: HashMap::default(),
+            started: 0,
+            dropped: 0,
+            stolen: 0,
+        }
+    }
+}
+
+/// How a [`VoicePool`] chooses a playing sound to stop in favour of a new one.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum VoiceStealing {
+    /// Never stop a playing sound. New sounds are dropped instead.
+    None,
+    /// Stop the sound which started playing first.
+    #[default]
+    Oldest,
+    /// Stop the sound with the lowest volume, preferring the oldest of equally quiet sounds.
+    Quietest,
+}
+
+struct Voice {
+    channel: ChannelId,
+    source: usize,
+    priority: u8,
+    volume: Num<i16, 8>,
+    started: u32,
+}
+
+/// The result of [allocating](VoicePool::allocate) a voice for a new sound.
+pub(crate) enum Allocation {
+    /// A voice is free.
+    Free,
+    /// The sound played by this entity must be stopped first.
+    Steal(Entity),
+    /// The sound can't be played.
+    Drop,
+}
+
+impl VoicePool {
+    /// Number of sounds dropped each frame because no voice was available.
+    pub const DROPPED_SOUNDS: DiagnosticPath = DiagnosticPath::const_new("audio/dropped_sounds");
+
+    /// Number of playing sounds stopped each frame to make room for new ones.
+    pub const STOLEN_VOICES: DiagnosticPath = DiagnosticPath::const_new("audio/stolen_voices");
+
+    /// Number of sound effects currently playing.
+    pub fn len(&self) -> usize {
+        self.voices.len()
+    }
+
+    /// Returns `true` if no sound effects are playing.
+    pub fn is_empty(&self) -> bool {
+        self.voices.is_empty()
+    }
+
+    pub(crate) fn channel(&self, entity: Entity) -> Option<&ChannelId> {
+        self.voices.get(&entity).map(|voice| &voice.channel)
+    }
+
+    pub(crate) fn set_volume(&mut self, entity: Entity, volume: Num<i16, 8>) {
+        if let Some(voice) = self.voices.get_mut(&entity) {
+            voice.volume = volume;
+        }
+    }
+
+    /// Finds a voice for a new sound, while `music` voices are being used by
+    /// [`Music`](super::Music).
+    pub(crate) fn allocate(
+        &self,
+        source: &GbaAudioSource,
+        settings: &PlaybackSettings,
+        music: usize,
+    ) -> Allocation {
+        let address = source.data().as_ptr().addr();
+        let stealable = self
+            .voices
+            .iter()
+            .filter(|(_, voice)| voice.priority <= settings.priority);
+
+        if let Some(max_instances) = settings.max_instances {
+            let instances = self
+                .voices
+                .values()
+                .filter(|voice| voice.source == address)
+                .count();
+
+            if instances >= usize::from(max_instances) {
+                return self.victim(stealable.filter(|(_, voice)| voice.source == address));
+            }
+        }
+
+        if self.voices.len() + music >= self.max_voices {
+            return self.victim(stealable);
+        }
+
+        Allocation::Free
+    }
+
+    fn victim<'a>(&self, candidates: impl Iterator<Item = (&'a Entity, &'a Voice)>) -> Allocation {
+        let victim = match self.stealing {
+            VoiceStealing::None => None,
+            VoiceStealing::Oldest => candidates.min_by_key(|(_, voice)| voice.started),
+            VoiceStealing::Quietest => {
+                candidates.min_by_key(|(_, voice)| (voice.volume, voice.started))
+            }
+        };
+
+        victim.map_or(Allocation::Drop, |(&entity, _)| Allocation::Steal(entity))
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        entity: Entity,
+        channel: ChannelId,
+        source: &GbaAudioSource,
+        settings: &PlaybackSettings,
+        volume: Num<i16, 8>,
+    ) {
+        self.started = self.started.wrapping_add(1);
+
+        self.voices.insert(
+            entity,
+            Voice {
+                channel,
+                source: source.data().as_ptr().addr(),
+                priority: settings.priority,
+                volume,
+                started: self.started,
+            },
+        );
+    }
+
+    pub(crate) fn remove(&mut self, entity: Entity) -> Option<ChannelId> {
+        self.voices.remove(&entity).map(|voice| voice.channel)
+    }
+
+    pub(crate) fn record_dropped(&mut self) {
+        self.dropped += 1;
+    }
+
+    pub(crate) fn record_stolen(&mut self) {
+        self.stolen += 1;
+    }
+}
+
+pub(crate) fn measure_voice_pool(mut pool: ResMut<VoicePool>, mut diagnostics: Diagnostics) {
+    let pool = pool.bypass_change_detection();
+
+    let dropped = core::mem::take(&mut pool.dropped);
+    let stolen = core::mem::take(&mut pool.stolen);
+
+    diagnostics.add_measurement(&VoicePool::DROPPED_SOUNDS, || f64::from(dropped));
+    diagnostics.add_measurement(&VoicePool::STOLEN_VOICES, || f64::from(stolen));
+}
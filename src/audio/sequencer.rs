@@ -0,0 +1,337 @@
+use agb::sound::dmg::DutyCycle;
+use bevy::prelude::*;
+
+use super::{
+    AudioPause, Channel, DmgChannel, DmgChannels, DmgDirection, DmgEnvelope, DmgSfx, DmgSweep,
+    Noise,
+};
+
+/// Period of each note in the octave starting at C2, as `131072 / hz`, with 4 fractional bits.
+const C2_PERIODS: [u32; 12] = [
+    32063, 30264, 28565, 26962, 25449, 24020, 22672, 21400, 20199, 19065, 17995, 16985,
+];
+
+/// The lowest note the square channels can play.
+const C2: u8 = 36;
+
+/// A note played by a [`DmgSequencer`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DmgNote(u8);
+
+impl DmgNote {
+    /// Creates a note from its MIDI note number, where 60 is middle C.
+    /// The square channels can play notes from C2 (36) upwards.
+    pub const fn from_midi(note: u8) -> Self {
+        Self(note)
+    }
+
+    /// Creates a note from its octave and semitone within the octave, where `C4` is middle C.
+    pub const fn new(octave: u8, semitone: u8) -> Self {
+        Self((octave + 1) * 12 + semitone)
+    }
+
+    /// Creates a note for the [`Noise`] channel.
+    /// See [`play_sound`](agb::sound::dmg::Noise::play_sound) for details on each parameter.
+    pub const fn noise(frequency_divider: u8, shift_clock_frequency: u8) -> Self {
+        Self((shift_clock_frequency << 3) | (frequency_divider & 0b111))
+    }
+
+    /// The MIDI note number of this note.
+    pub const fn midi(self) -> u8 {
+        self.0
+    }
+
+    /// The value written to the frequency register of a square channel to play this note.
+    pub const fn square_frequency(self) -> u16 {
+        let note = self.0.saturating_sub(C2);
+        let octave = (note / 12) as u32;
+        let period = C2_PERIODS[(note % 12) as usize];
+        let period = (period + (1 << (3 + octave))) >> (4 + octave);
+
+        2048 - if period > 0 { period as u16 } else { 1 }
+    }
+}
+
+/// How a [`DmgSequencer`] plays notes on a channel.
+#[derive(Clone, Copy)]
+pub struct DmgInstrument {
+    /// How the volume of each note changes over time.
+    pub envelope: DmgEnvelope,
+    /// If set, each note stops after `(64 - length) / 256` seconds.
+    /// Must be less than 64.
+    pub length: Option<u8>,
+    /// Shape of the square wave. Ignored by the [`Noise`] channel.
+    pub duty: DutyCycle,
+    /// Frequency sweep applied over time. Only used by [`Channel<1>`].
+    pub sweep: DmgSweep,
+    /// Whether the [`Noise`] channel uses a 15 bit counter rather than a 7 bit counter.
+    pub counter_step_width_15: bool,
+}
+
+impl DmgInstrument {
+    /// Creates an instrument with the provided envelope.
+    pub const fn new(envelope: DmgEnvelope) -> Self {
+        Self {
+            envelope,
+            length: None,
+            duty: DutyCycle::Half,
+            sweep: DmgSweep::NONE,
+            counter_step_width_15: false,
+        }
+    }
+
+    /// Sets the [length](Self::length) of each note.
+    pub const fn with_length(mut self, length: u8) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// Sets the [duty cycle](Self::duty) of the square wave.
+    pub const fn with_duty(mut self, duty: DutyCycle) -> Self {
+        self.duty = duty;
+        self
+    }
+
+    /// Sets the [frequency sweep](Self::sweep) used by [`Channel<1>`].
+    pub const fn with_sweep(mut self, sweep: DmgSweep) -> Self {
+        self.sweep = sweep;
+        self
+    }
+
+    /// Uses a [15 bit counter](Self::counter_step_width_15) on the [`Noise`] channel.
+    pub const fn with_counter_step_width_15(mut self) -> Self {
+        self.counter_step_width_15 = true;
+        self
+    }
+
+    /// Creates the effect which plays `note` with this instrument on `channel`.
+    pub const fn sfx(&self, channel: DmgChannel, note: DmgNote) -> DmgSfx {
+        let sfx = match channel {
+            DmgChannel::Square1 => DmgSfx::square1(note.square_frequency(), self.sweep, self.duty),
+            DmgChannel::Square2 => DmgSfx::square2(note.square_frequency(), self.duty),
+            DmgChannel::Noise => DmgSfx::noise(
+                note.0 & 0b111,
+                self.counter_step_width_15,
+                (note.0 >> 3) & 0b1111,
+            ),
+        };
+
+        let sfx = sfx.with_envelope(self.envelope);
+
+        match self.length {
+            Some(length) => sfx.with_length(length),
+            None => sfx,
+        }
+    }
+}
+
+/// What a channel does on one row of a [`DmgPattern`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DmgStep {
+    /// Leave the channel as it is, letting the previous note continue.
+    Continue,
+    /// Play a note with an instrument from the [song](DmgSong::instruments).
+    Note {
+        /// The note to play.
+        note: DmgNote,
+        /// Index of the instrument in the song.
+        instrument: u8,
+    },
+    /// Silence the channel.
+    Release,
+}
+
+impl DmgStep {
+    /// Creates a step which plays `note` with the provided instrument.
+    pub const fn note(note: DmgNote, instrument: u8) -> Self {
+        Self::Note { note, instrument }
+    }
+}
+
+/// A sequence of rows, each holding a [`DmgStep`] for [`Channel<1>`], [`Channel<2>`], and
+/// [`Noise`], in that order.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DmgPattern {
+    /// The rows of this pattern.
+    pub rows: &'static [[DmgStep; 3]],
+}
+
+/// Music for the DMG sound hardware, played by a [`DmgSequencer`].
+///
+/// Songs are plain data, so they can be stored in ROM as `static` items.
+#[derive(Clone, Copy)]
+pub struct DmgSong {
+    /// Number of frames each row plays for. Lower values play faster.
+    pub frames_per_row: u8,
+    /// Instruments referenced by the patterns.
+    pub instruments: &'static [DmgInstrument],
+    /// Patterns referenced by the order list.
+    pub patterns: &'static [DmgPattern],
+    /// Indices into [`patterns`](Self::patterns), played in order.
+    pub order: &'static [u8],
+    /// If set, playback continues from this index into the [order list](Self::order) once the
+    /// song ends. Otherwise, or if the index is out of range, the song stops.
+    pub restart: Option<u8>,
+}
+
+/// Plays a [`DmgSong`] on [`Channel<1>`], [`Channel<2>`], and [`Noise`], one row at a time.
+///
+/// Effects requested with [`PlayDmgSound`](super::PlayDmgSound) take priority: while a channel is
+/// [playing an effect](DmgChannels::is_playing), the sequencer skips its notes.
+#[derive(Resource, Default)]
+pub struct DmgSequencer {
+    song: Option<&'static DmgSong>,
+    order: usize,
+    row: usize,
+    frame: u8,
+    frames_per_row: u8,
+    paused: bool,
+    silence: bool,
+}
+
+impl DmgSequencer {
+    /// Starts playing `song` from the beginning.
+    pub fn play(&mut self, song: &'static DmgSong) {
+        *self = Self {
+            song: Some(song),
+            frames_per_row: song.frames_per_row,
+            silence: true,
+            ..default()
+        };
+    }
+
+    /// Stops the current song, silencing its channels.
+    pub fn stop(&mut self) {
+        self.song = None;
+        self.silence = true;
+    }
+
+    /// Pauses the current song, silencing its channels until it is [resumed](Self::resume).
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.silence = true;
+    }
+
+    /// Resumes the current song after it was [paused](Self::pause).
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Returns `true` if a song is playing and not paused.
+    pub fn is_playing(&self) -> bool {
+        self.song.is_some() && !self.paused
+    }
+
+    /// The song currently loaded, if any.
+    pub fn song(&self) -> Option<&'static DmgSong> {
+        self.song
+    }
+
+    /// The current index into the order list and row within the pattern.
+    pub fn position(&self) -> (usize, usize) {
+        (self.order, self.row)
+    }
+
+    /// Number of frames each row currently plays for.
+    pub fn frames_per_row(&self) -> u8 {
+        self.frames_per_row
+    }
+
+    /// Changes the tempo of the current song, playing each row for `frames_per_row` frames.
+    pub fn set_frames_per_row(&mut self, frames_per_row: u8) {
+        self.frames_per_row = frames_per_row.max(1);
+    }
+}
+
+const SEQUENCER_CHANNELS: [DmgChannel; 3] =
+    [DmgChannel::Square1, DmgChannel::Square2, DmgChannel::Noise];
+
+const SILENCE: DmgInstrument = DmgInstrument::new(DmgEnvelope::new(0, DmgDirection::Decrease, 0));
+
+pub(crate) fn tick_dmg_sequencer(
+    mut sequencer: ResMut<DmgSequencer>,
+    pause: Res<AudioPause>,
+    channels: Res<DmgChannels>,
+    channel1: Option<Res<Channel<1>>>,
+    channel2: Option<Res<Channel<2>>>,
+    noise: Option<Res<Noise>>,
+) {
+    let (channel1, channel2, noise) = (channel1.as_deref(), channel2.as_deref(), noise.as_deref());
+
+    if sequencer.silence {
+        sequencer.silence = false;
+
+        for channel in SEQUENCER_CHANNELS {
+            if !channels.is_playing(channel) {
+                SILENCE
+                    .sfx(channel, DmgNote(0))
+                    .play(channel1, channel2, noise);
+            }
+        }
+    }
+
+    let Some(song) = sequencer.song else {
+        return;
+    };
+
+    if sequencer.paused || pause.is_paused() {
+        return;
+    }
+
+    if sequencer.frame > 0 {
+        sequencer.frame -= 1;
+        return;
+    }
+
+    sequencer.frame = sequencer.frames_per_row.saturating_sub(1);
+
+    // An empty order list, or one which refers to a missing pattern, ends the song.
+    let Some(pattern) = song
+        .order
+        .get(sequencer.order)
+        .and_then(|&pattern| song.patterns.get(usize::from(pattern)))
+    else {
+        sequencer.stop();
+        return;
+    };
+
+    if let Some(row) = pattern.rows.get(sequencer.row) {
+        for (channel, step) in SEQUENCER_CHANNELS.into_iter().zip(row) {
+            if channels.is_playing(channel) {
+                continue;
+            }
+
+            let sfx = match *step {
+                DmgStep::Continue => continue,
+                DmgStep::Note { note, instrument } => {
+                    let Some(instrument) = song.instruments.get(usize::from(instrument)) else {
+                        continue;
+                    };
+
+                    instrument.sfx(channel, note)
+                }
+                DmgStep::Release => SILENCE.sfx(channel, DmgNote(0)),
+            };
+
+            sfx.play(channel1, channel2, noise);
+        }
+    }
+
+    sequencer.row += 1;
+
+    if sequencer.row < pattern.rows.len() {
+        return;
+    }
+
+    sequencer.row = 0;
+    sequencer.order += 1;
+
+    if sequencer.order < song.order.len() {
+        return;
+    }
+
+    match song.restart {
+        Some(restart) => sequencer.order = usize::from(restart),
+        None => sequencer.stop(),
+    }
+}
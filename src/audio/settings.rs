@@ -0,0 +1,215 @@
+#![expect(
+    unsafe_code,
+    reason = "the DMG master volume is controlled through memory mapped registers"
+)]
+
+use agb::fixnum::Num;
+use bevy::prelude::*;
+
+use super::{AudioPause, AudioSink, Sound};
+
+const MASTER_SOUND_VOLUME_ENABLE: *mut u16 = 0x0400_0080 as *mut u16;
+
+const DMG_VOLUME_MASK: u16 = 0b0111_0111;
+const DMG_ENABLE_MASK: u16 = 0xFF00;
+
+/// Global volume controls, applied on top of the volume of each sound.
+///
+/// Each category is scaled by the [master](Self::master) volume, where 1 is full volume and 0 is
+/// silent:
+/// * [`music`](Self::music) scales [`Music`](super::Music).
+/// * [`sfx`](Self::sfx) scales sounds played by an [`AudioPlayer`](super::AudioPlayer).
+/// * [`dmg`](Self::dmg) sets the master volume of the DMG sound hardware, in 8 steps.
+///
+/// Music can also be temporarily [ducked](Ducking) while dialogue or jingles play, either with
+/// [`duck_for`](Self::duck_for) or by adding [`DuckMusic`] to an
+/// [`AudioPlayer`](super::AudioPlayer).
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct AudioSettings {
+    /// Volume of all audio.
+    pub master: Num<i16, 8>,
+    /// Volume of tracker music.
+    pub music: Num<i16, 8>,
+    /// Volume of direct sound effects.
+    pub sfx: Num<i16, 8>,
+    /// Volume of the DMG sound hardware.
+    pub dmg: Num<i16, 8>,
+    /// If `true`, all audio is silenced without changing the volume of any category.
+    pub muted: bool,
+    /// How music is ducked.
+    pub ducking: Ducking,
+    duck_level: Num<i16, 8>,
+    duck_frames: u32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: Num::new(1),
+            music: Num::new(1),
+            sfx: Num::new(1),
+            dmg: Num::new(1),
+            muted: false,
+            ducking: Ducking::default(),
+            duck_level: Num::new(1),
+            duck_frames: 0,
+        }
+    }
+}
+
+impl AudioSettings {
+    fn scaled(&self, volume: Num<i16, 8>) -> Num<i16, 8> {
+        if self.muted {
+            Num::new(0)
+        } else {
+            self.master * volume
+        }
+    }
+
+    /// The final volume multiplier applied to music, including any ducking.
+    pub fn music_volume(&self) -> Num<i16, 8> {
+        self.scaled(self.music) * self.duck_level
+    }
+
+    /// The final volume multiplier applied to sound effects.
+    pub fn sfx_volume(&self) -> Num<i16, 8> {
+        self.scaled(self.sfx)
+    }
+
+    /// The final volume applied to the DMG sound hardware.
+    pub fn dmg_volume(&self) -> Num<i16, 8> {
+        self.scaled(self.dmg)
+    }
+
+    /// Ducks music for at least `frames` frames, such as while a DMG jingle plays.
+    pub fn duck_for(&mut self, frames: u32) {
+        self.duck_frames = self.duck_frames.max(frames);
+    }
+
+    /// Returns `true` if music is currently ducked or fading back in.
+    pub fn is_ducked(&self) -> bool {
+        self.duck_level < Num::new(1)
+    }
+}
+
+/// Controls how music is ducked.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Ducking {
+    /// Volume multiplier applied to music while ducked.
+    pub volume: Num<i16, 8>,
+    /// Number of frames taken to fade music down.
+    pub attack: u32,
+    /// Number of frames taken to fade music back up once ducking ends.
+    pub release: u32,
+}
+
+impl Default for Ducking {
+    fn default() -> Self {
+        Self {
+            volume: Num::from_raw(1 << 6),
+            attack: 6,
+            release: 30,
+        }
+    }
+}
+
+/// Ducks music while the [`AudioPlayer`](super::AudioPlayer) on this entity is playing.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct DuckMusic;
+
+pub(crate) fn update_ducking(
+    mut settings: ResMut<AudioSettings>,
+    duckers: Query<&AudioSink, With<DuckMusic>>,
+) {
+    let held = settings.duck_frames > 0;
+
+    if held {
+        settings.bypass_change_detection().duck_frames -= 1;
+    }
+
+    let ducked = held
+        || duckers
+            .iter()
+            .any(|sink| !sink.is_finished() && !sink.paused);
+
+    let Ducking {
+        volume,
+        attack,
+        release,
+    } = settings.ducking;
+
+    let (target, frames) = if ducked {
+        (volume, attack)
+    } else {
+        (Num::new(1), release)
+    };
+
+    let current = settings.duck_level;
+
+    if current == target {
+        return;
+    }
+
+    let distance = (target - current).abs();
+    let step = if frames == 0 {
+        distance
+    } else {
+        let frames = i16::try_from(frames).unwrap_or(i16::MAX);
+        ((Num::new(1) - volume).abs() / frames).max(Num::from_raw(1))
+    };
+
+    settings.duck_level = if current < target {
+        (current + step).min(target)
+    } else {
+        (current - step).max(target)
+    };
+}
+
+pub(crate) fn apply_dmg_volume(
+    settings: Res<AudioSettings>,
+    pause: Res<AudioPause>,
+    sound: Option<Res<Sound>>,
+    mut muted_channels: Local<Option<u16>>,
+    mut applied: Local<Option<Num<i16, 8>>>,
+) {
+    if sound.is_none() || !(settings.is_changed() || pause.is_changed()) {
+        return;
+    }
+
+    let volume = if pause.is_paused() {
+        Num::new(0)
+    } else {
+        settings.dmg_volume()
+    };
+
+    // The hardware is left as the game set it up until the volume actually changes from full.
+    if applied.unwrap_or(Num::new(1)) == volume {
+        return;
+    }
+
+    *applied = Some(volume);
+
+    // Each side has 8 volume steps, from 1/8 to full volume, so silence requires disabling the
+    // channels entirely.
+    let steps = (volume * 8 + Num::from_raw(1 << 7)).floor() - 1;
+
+    // SAFETY: The master sound registers are always valid to read and write.
+    unsafe {
+        let enable = MASTER_SOUND_VOLUME_ENABLE.read_volatile();
+
+        let enable = if steps < 0 {
+            if muted_channels.is_none() {
+                *muted_channels = Some(enable & DMG_ENABLE_MASK);
+            }
+
+            enable & !(DMG_ENABLE_MASK | DMG_VOLUME_MASK)
+        } else {
+            let channels = muted_channels.take().unwrap_or(enable & DMG_ENABLE_MASK);
+            let side = steps.min(7) as u16;
+
+            (enable & !(DMG_ENABLE_MASK | DMG_VOLUME_MASK)) | channels | side | (side << 4)
+        };
+
+        MASTER_SOUND_VOLUME_ENABLE.write_volatile(enable);
+    }
+}
@@ -0,0 +1,395 @@
+use agb::sound::dmg::{DutyCycle, EnvelopeSettings, SoundDirection, SweepSettings};
+use bevy::prelude::*;
+
+use super::{AudioPause, Channel, Noise};
+
+/// Approximate number of frames per second, used to estimate how long a [`DmgSfx`] will play for.
+const FRAMES_PER_SECOND: u32 = 60;
+
+/// A reusable sound effect for the DMG sound hardware, defined as data.
+///
+/// ```ignore
+/// const JUMP: DmgSfx = DmgSfx::noise(0, false, 0)
+///     .with_envelope(DmgEnvelope::new(1, DmgDirection::Decrease, 4))
+///     .with_length(32);
+/// ```
+#[derive(Clone, Copy)]
+pub struct DmgSfx {
+    /// The channel and channel-specific settings used to play this effect.
+    pub voice: DmgVoice,
+    /// How the volume of this effect changes over time.
+    pub envelope: DmgEnvelope,
+    /// If set, the effect stops after `(64 - length) / 256` seconds.
+    /// Must be less than 64.
+    pub length: Option<u8>,
+}
+
+impl DmgSfx {
+    /// Creates a sound effect for [`Channel<1>`], which supports a frequency [sweep](DmgSweep).
+    pub const fn square1(frequency: u16, sweep: DmgSweep, duty: DutyCycle) -> Self {
+        Self::new(DmgVoice::Square1 {
+            frequency,
+            sweep,
+            duty,
+        })
+    }
+
+    /// Creates a sound effect for [`Channel<2>`].
+    pub const fn square2(frequency: u16, duty: DutyCycle) -> Self {
+        Self::new(DmgVoice::Square2 { frequency, duty })
+    }
+
+    /// Creates a sound effect for the [`Noise`] channel.
+    /// See [`play_sound`](agb::sound::dmg::Noise::play_sound) for details on each parameter.
+    pub const fn noise(
+        frequency_divider: u8,
+        counter_step_width_15: bool,
+        shift_clock_frequency: u8,
+    ) -> Self {
+        Self::new(DmgVoice::Noise {
+            frequency_divider,
+            counter_step_width_15,
+            shift_clock_frequency,
+        })
+    }
+
+    const fn new(voice: DmgVoice) -> Self {
+        Self {
+            voice,
+            envelope: DmgEnvelope::new(0, DmgDirection::Increase, 15),
+            length: None,
+        }
+    }
+
+    /// Sets the [envelope](Self::envelope) of this effect.
+    pub const fn with_envelope(mut self, envelope: DmgEnvelope) -> Self {
+        self.envelope = envelope;
+        self
+    }
+
+    /// Sets the [length](Self::length) of this effect.
+    pub const fn with_length(mut self, length: u8) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// The channel this effect plays on.
+    pub const fn channel(&self) -> DmgChannel {
+        match self.voice {
+            DmgVoice::Square1 { .. } => DmgChannel::Square1,
+            DmgVoice::Square2 { .. } => DmgChannel::Square2,
+            DmgVoice::Noise { .. } => DmgChannel::Noise,
+        }
+    }
+
+    /// Plays this effect on the appropriate channel, returning `false` if it isn't available.
+    pub(crate) fn play(
+        &self,
+        channel1: Option<&Channel<1>>,
+        channel2: Option<&Channel<2>>,
+        noise: Option<&Noise>,
+    ) -> bool {
+        let envelope = self.envelope.to_agb();
+
+        match self.voice {
+            DmgVoice::Square1 {
+                frequency,
+                sweep,
+                duty,
+            } => {
+                let Some(channel1) = channel1 else {
+                    return false;
+                };
+
+                channel1.play_sound(frequency, self.length, &sweep.to_agb(), &envelope, duty);
+            }
+            DmgVoice::Square2 { frequency, duty } => {
+                let Some(channel2) = channel2 else {
+                    return false;
+                };
+
+                channel2.play_sound(frequency, self.length, &envelope, duty);
+            }
+            DmgVoice::Noise {
+                frequency_divider,
+                counter_step_width_15,
+                shift_clock_frequency,
+            } => {
+                let Some(noise) = noise else {
+                    return false;
+                };
+
+                noise.play_sound(
+                    self.length,
+                    &envelope,
+                    frequency_divider,
+                    counter_step_width_15,
+                    shift_clock_frequency,
+                );
+            }
+        }
+
+        true
+    }
+
+    /// Estimates how many frames this effect will be audible for.
+    /// Returns [`None`] if the effect plays until it is replaced.
+    pub fn frames(&self) -> Option<u32> {
+        let length = self
+            .length
+            .map(|length| (64 - u32::from(length)) * FRAMES_PER_SECOND / 256);
+
+        let envelope = (self.envelope.step_time > 0
+            && matches!(self.envelope.direction, DmgDirection::Decrease))
+        .then(|| {
+            u32::from(self.envelope.initial_volume)
+                * u32::from(self.envelope.step_time)
+                * FRAMES_PER_SECOND
+                / 64
+        });
+
+        match (length, envelope) {
+            (Some(length), Some(envelope)) => Some(length.min(envelope)),
+            (length, envelope) => length.or(envelope),
+        }
+    }
+}
+
+/// The channel and channel-specific settings of a [`DmgSfx`].
+#[derive(Clone, Copy)]
+pub enum DmgVoice {
+    /// Played on [`Channel<1>`].
+    Square1 {
+        /// Must be less than 2048.
+        frequency: u16,
+        /// Frequency sweep applied over time.
+        sweep: DmgSweep,
+        /// Shape of the square wave.
+        duty: DutyCycle,
+    },
+    /// Played on [`Channel<2>`].
+    Square2 {
+        /// Must be less than 2048.
+        frequency: u16,
+        /// Shape of the square wave.
+        duty: DutyCycle,
+    },
+    /// Played on the [`Noise`] channel.
+    Noise {
+        /// Must be less than 8.
+        frequency_divider: u8,
+        /// Whether to use a 15 bit counter rather than a 7 bit counter.
+        counter_step_width_15: bool,
+        /// Must be less than 16.
+        shift_clock_frequency: u8,
+    },
+}
+
+/// Whether an envelope or sweep increases or decreases over time.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DmgDirection {
+    /// Increase over time.
+    Increase,
+    /// Decrease over time.
+    Decrease,
+}
+
+impl DmgDirection {
+    const fn to_agb(self) -> SoundDirection {
+        match self {
+            Self::Increase => SoundDirection::Increase,
+            Self::Decrease => SoundDirection::Decrease,
+        }
+    }
+}
+
+/// A copyable equivalent of [`EnvelopeSettings`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DmgEnvelope {
+    /// Time between each volume step in 64ths of a second, or 0 to keep a constant volume.
+    /// Must be less than 8.
+    pub step_time: u8,
+    /// Whether the volume increases or decreases with each step.
+    pub direction: DmgDirection,
+    /// Starting volume. Must be less than 16.
+    pub initial_volume: u8,
+}
+
+impl DmgEnvelope {
+    /// Creates a new [`DmgEnvelope`].
+    pub const fn new(step_time: u8, direction: DmgDirection, initial_volume: u8) -> Self {
+        Self {
+            step_time,
+            direction,
+            initial_volume,
+        }
+    }
+
+    /// Converts these settings for use with [`agb`].
+    pub fn to_agb(self) -> EnvelopeSettings {
+        EnvelopeSettings::new(self.step_time, self.direction.to_agb(), self.initial_volume)
+    }
+}
+
+/// A copyable equivalent of [`SweepSettings`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DmgSweep {
+    /// Must be less than 8.
+    pub number_of_sweep_shifts: u8,
+    /// Whether the frequency increases or decreases over time.
+    pub direction: DmgDirection,
+    /// Time between each sweep step in 128ths of a second, or 0 to disable the sweep.
+    /// Must be less than 8.
+    pub sweep_time: u8,
+}
+
+impl DmgSweep {
+    /// A sweep which leaves the frequency unchanged.
+    pub const NONE: Self = Self::new(0, DmgDirection::Increase, 0);
+
+    /// Creates a new [`DmgSweep`].
+    pub const fn new(number_of_sweep_shifts: u8, direction: DmgDirection, sweep_time: u8) -> Self {
+        Self {
+            number_of_sweep_shifts,
+            direction,
+            sweep_time,
+        }
+    }
+
+    /// Converts these settings for use with [`agb`].
+    pub fn to_agb(self) -> SweepSettings {
+        SweepSettings::new(
+            self.number_of_sweep_shifts,
+            self.direction.to_agb(),
+            self.sweep_time,
+        )
+    }
+}
+
+impl Default for DmgSweep {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// A DMG sound channel which can play a [`DmgSfx`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DmgChannel {
+    /// [`Channel<1>`].
+    Square1,
+    /// [`Channel<2>`].
+    Square2,
+    /// [`Noise`].
+    Noise,
+}
+
+impl DmgChannel {
+    const fn index(self) -> usize {
+        match self {
+            Self::Square1 => 0,
+            Self::Square2 => 1,
+            Self::Noise => 2,
+        }
+    }
+}
+
+/// Requests a [`DmgSfx`] be played.
+///
+/// If the channel is already playing an effect with a higher priority, this request is ignored.
+/// Otherwise, the current effect is replaced.
+///
+/// Effects with no [length](DmgSfx::length) whose volume never fades out play until they're
+/// replaced, so only hold their channel for the frame they start on.
+/// Requests sent while [audio is paused](AudioPause) are ignored.
+#[derive(Event, Clone, Copy)]
+pub struct PlayDmgSound {
+    /// The effect to play.
+    pub sfx: DmgSfx,
+    /// Priority of this effect. Higher priorities can interrupt lower priorities.
+    pub priority: u8,
+}
+
+impl PlayDmgSound {
+    /// Creates a request to play `sfx` with the lowest priority.
+    pub const fn new(sfx: DmgSfx) -> Self {
+        Self { sfx, priority: 0 }
+    }
+
+    /// Sets the [priority](Self::priority) of this request.
+    pub const fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Tracks which [`DmgSfx`] currently owns each DMG channel.
+#[derive(Resource, Default, Debug)]
+pub struct DmgChannels {
+    active: [Option<ActiveSfx>; 3],
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ActiveSfx {
+    priority: u8,
+    remaining: u32,
+}
+
+impl DmgChannels {
+    /// Returns `true` if `channel` is currently playing an effect.
+    pub fn is_playing(&self, channel: DmgChannel) -> bool {
+        self.active[channel.index()].is_some()
+    }
+
+    /// Gets the priority of the effect currently playing on `channel`, if any.
+    pub fn priority(&self, channel: DmgChannel) -> Option<u8> {
+        self.active[channel.index()].map(|active| active.priority)
+    }
+
+    /// Marks `channel` as free, allowing any effect to play on it.
+    pub fn release(&mut self, channel: DmgChannel) {
+        self.active[channel.index()] = None;
+    }
+}
+
+pub(crate) fn play_dmg_sounds(
+    mut events: EventReader<PlayDmgSound>,
+    mut channels: ResMut<DmgChannels>,
+    channel1: Option<Res<Channel<1>>>,
+    channel2: Option<Res<Channel<2>>>,
+    noise: Option<Res<Noise>>,
+    pause: Res<AudioPause>,
+) {
+    if pause.is_paused() {
+        events.clear();
+        return;
+    }
+
+    for active in channels.active.iter_mut() {
+        let Some(ActiveSfx { remaining, .. }) = active else {
+            continue;
+        };
+
+        if *remaining == 0 {
+            *active = None;
+        } else {
+            *remaining -= 1;
+        }
+    }
+
+    for &PlayDmgSound { sfx, priority } in events.read() {
+        let active = &mut channels.active[sfx.channel().index()];
+
+        if active.is_some_and(|active| active.priority > priority) {
+            continue;
+        }
+
+        if !sfx.play(channel1.as_deref(), channel2.as_deref(), noise.as_deref()) {
+            continue;
+        }
+
+        *active = Some(ActiveSfx {
+            priority,
+            remaining: sfx.frames().unwrap_or(0),
+        });
+    }
+}
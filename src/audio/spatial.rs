@@ -0,0 +1,119 @@
+use agb::fixnum::Num;
+use bevy::prelude::*;
+
+use super::{AudioSink, PlaybackSettings};
+
+/// Marks the entity sounds are heard from, such as the player or the camera.
+/// Only one listener should exist at a time.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[require(Transform)]
+pub struct SoundListener;
+
+/// Pans and attenuates the [`AudioPlayer`](super::AudioPlayer) on this entity based on its
+/// position relative to the [`SoundListener`].
+///
+/// While an emitter is present, the [volume](AudioSink::volume) and
+/// [panning](AudioSink::panning) of the [`AudioSink`] are managed automatically, scaling the
+/// [volume](PlaybackSettings::volume) the sound started with.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+#[require(Transform)]
+pub struct SoundEmitter {
+    /// Distance in pixels within which the sound plays at full volume.
+    pub radius: f32,
+    /// Distance in pixels beyond which the sound is silent.
+    pub range: f32,
+    /// Horizontal distance in pixels at which the sound is panned entirely to one side.
+    pub pan_distance: f32,
+}
+
+impl Default for SoundEmitter {
+    fn default() -> Self {
+        Self {
+            radius: 16.,
+            range: agb::display::WIDTH as f32,
+            pan_distance: agb::display::WIDTH as f32 / 2.,
+        }
+    }
+}
+
+impl SoundEmitter {
+    /// Creates an emitter which is silent beyond `range` pixels from the listener.
+    pub fn new(range: f32) -> Self {
+        Self { range, ..default() }
+    }
+
+    /// Sets the [radius](Self::radius) of this emitter.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets the [pan distance](Self::pan_distance) of this emitter.
+    pub fn with_pan_distance(mut self, pan_distance: f32) -> Self {
+        self.pan_distance = pan_distance;
+        self
+    }
+
+    /// Volume multiplier for a sound `offset` pixels away from the listener, falling off
+    /// linearly from 1 at the [radius](Self::radius) to 0 at the [range](Self::range).
+    pub fn attenuation(&self, offset: Vec2) -> Num<i16, 8> {
+        let distance = offset.length();
+
+        let attenuation = if distance <= self.radius {
+            1.
+        } else if distance >= self.range {
+            0.
+        } else {
+            1. - (distance - self.radius) / (self.range - self.radius)
+        };
+
+        Num::from_f32(attenuation)
+    }
+
+    /// Panning for a sound `offset` pixels away from the listener, from -1 (left) to 1 (right).
+    pub fn panning(&self, offset: Vec2) -> Num<i16, 8> {
+        if offset.x == 0. {
+            return Num::new(0);
+        }
+
+        if self.pan_distance <= 0. {
+            return Num::new(offset.x.signum() as i16);
+        }
+
+        Num::from_f32((offset.x / self.pan_distance).clamp(-1., 1.))
+    }
+
+    pub(crate) fn spatialize(
+        &self,
+        emitter: &GlobalTransform,
+        listener: &GlobalTransform,
+        volume: Num<i16, 8>,
+    ) -> (Num<i16, 8>, Num<i16, 8>) {
+        let offset = (emitter.translation() - listener.translation()).truncate();
+
+        (volume * self.attenuation(offset), self.panning(offset))
+    }
+}
+
+pub(crate) fn apply_sound_emitters(
+    listeners: Query<&GlobalTransform, With<SoundListener>>,
+    mut emitters: Query<(
+        &SoundEmitter,
+        &GlobalTransform,
+        &PlaybackSettings,
+        &mut AudioSink,
+    )>,
+) {
+    let Ok(listener) = listeners.single() else {
+        return;
+    };
+
+    for (emitter, transform, settings, mut sink) in &mut emitters {
+        let (volume, panning) = emitter.spatialize(transform, listener, settings.volume);
+
+        if sink.volume != volume || sink.panning != panning {
+            sink.volume = volume;
+            sink.panning = panning;
+        }
+    }
+}
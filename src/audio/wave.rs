@@ -0,0 +1,175 @@
+#![expect(
+    unsafe_code,
+    reason = "the wave channel is controlled through memory mapped registers"
+)]
+
+const CHANNEL_3_SELECT: *mut u16 = 0x0400_0070 as *mut u16;
+const CHANNEL_3_LENGTH_VOLUME: *mut u16 = 0x0400_0072 as *mut u16;
+const CHANNEL_3_FREQUENCY_CONTROL: *mut u16 = 0x0400_0074 as *mut u16;
+const WAVE_RAM: *mut u16 = 0x0400_0090 as *mut u16;
+
+const TWO_BANKS_BIT: u16 = 1 << 5;
+const BANK_BIT: u16 = 1 << 6;
+const ENABLE_BIT: u16 = 1 << 7;
+
+/// 32 4-bit samples making up one bank of wave RAM.
+/// Each byte holds two samples, with the first sample in the upper 4 bits.
+pub type WaveData = [u8; 16];
+
+/// One of the two banks of wave RAM.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum WaveBank {
+    /// The first bank.
+    Bank0,
+    /// The second bank.
+    Bank1,
+}
+
+impl WaveBank {
+    const fn bits(self) -> u16 {
+        match self {
+            Self::Bank0 => 0,
+            Self::Bank1 => BANK_BIT,
+        }
+    }
+
+    const fn other(self) -> Self {
+        match self {
+            Self::Bank0 => Self::Bank1,
+            Self::Bank1 => Self::Bank0,
+        }
+    }
+}
+
+/// Output volume of the wave channel.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum WaveVolume {
+    /// Silent.
+    Mute,
+    /// 25% volume.
+    Quarter,
+    /// 50% volume.
+    Half,
+    /// 75% volume.
+    ThreeQuarters,
+    /// 100% volume.
+    #[default]
+    Full,
+}
+
+impl WaveVolume {
+    const fn bits(self) -> u16 {
+        match self {
+            Self::Mute => 0,
+            Self::Full => 1 << 13,
+            Self::Half => 2 << 13,
+            Self::Quarter => 3 << 13,
+            Self::ThreeQuarters => 1 << 15,
+        }
+    }
+}
+
+/// Controls the DMG's programmable wave channel (channel 3), which plays back 4-bit samples
+/// stored in wave RAM.
+#[non_exhaustive]
+pub struct WaveChannel {}
+
+impl WaveChannel {
+    pub(crate) const fn new() -> Self {
+        Self {}
+    }
+
+    fn select() -> u16 {
+        // SAFETY: The channel 3 select register is always valid to read.
+        unsafe { CHANNEL_3_SELECT.read_volatile() }
+    }
+
+    fn set_select(value: u16) {
+        // SAFETY: The channel 3 select register is always valid to write.
+        unsafe { CHANNEL_3_SELECT.write_volatile(value) }
+    }
+
+    /// Gets the bank currently being played.
+    pub fn bank(&self) -> WaveBank {
+        if Self::select() & BANK_BIT == 0 {
+            WaveBank::Bank0
+        } else {
+            WaveBank::Bank1
+        }
+    }
+
+    /// Writes `samples` into the provided `bank` of wave RAM.
+    ///
+    /// Only the bank which isn't being played can be written to, so writing to the playing bank
+    /// briefly switches playback to the other bank.
+    /// To avoid glitches, upload to the bank not being played and [switch](Self::set_bank) to it.
+    pub fn upload(&self, bank: WaveBank, samples: &WaveData) {
+        let select = Self::select();
+
+        Self::set_select((select & !BANK_BIT) | bank.other().bits());
+
+        for (index, pair) in samples.chunks_exact(2).enumerate() {
+            // Wave RAM stores samples in the order they appear in memory, so keep byte order.
+            let value = u16::from_le_bytes([pair[0], pair[1]]);
+
+            // SAFETY: Wave RAM is 16 bytes long, accessed as 8 halfwords.
+            unsafe { WAVE_RAM.add(index).write_volatile(value) };
+        }
+
+        Self::set_select(select);
+    }
+
+    /// Selects which bank of wave RAM is played.
+    /// When [two banks](Self::set_two_banks) are enabled, playback starts from this bank.
+    pub fn set_bank(&self, bank: WaveBank) {
+        Self::set_select((Self::select() & !BANK_BIT) | bank.bits());
+    }
+
+    /// If `true`, both banks are played back to back as a single 64 sample wave.
+    /// Otherwise, only the [selected bank](Self::set_bank) is played.
+    pub fn set_two_banks(&self, two_banks: bool) {
+        let select = Self::select() & !TWO_BANKS_BIT;
+
+        Self::set_select(if two_banks {
+            select | TWO_BANKS_BIT
+        } else {
+            select
+        });
+    }
+
+    /// Sets the output volume of this channel.
+    pub fn set_volume(&self, volume: WaveVolume) {
+        // SAFETY: The channel 3 length and volume register is always valid to read and write.
+        unsafe {
+            let current = CHANNEL_3_LENGTH_VOLUME.read_volatile();
+            CHANNEL_3_LENGTH_VOLUME.write_volatile((current & 0xFF) | volume.bits());
+        }
+    }
+
+    /// Starts playing the selected wave.
+    ///
+    /// `frequency` controls the sample rate as `2097152 / (2048 - frequency)` Hz, and must be
+    /// less than 2048.
+    /// If `length` is set, playback stops after `(256 - length) / 256` seconds.
+    pub fn play_sound(&self, frequency: u16, length: Option<u8>, volume: WaveVolume) {
+        assert!(frequency < 2048, "Frequency must be less than 2048");
+
+        let length_bits = u16::from(length.unwrap_or(0));
+        let length_flag: u16 = length.map_or(0, |_| 1 << 14);
+        let initial: u16 = 1 << 15;
+
+        Self::set_select(Self::select() | ENABLE_BIT);
+
+        // SAFETY: The channel 3 length, volume, and frequency registers are always valid to
+        // write.
+        unsafe {
+            CHANNEL_3_LENGTH_VOLUME.write_volatile(length_bits | volume.bits());
+            CHANNEL_3_FREQUENCY_CONTROL.write_volatile(frequency | length_flag | initial);
+        }
+    }
+
+    /// Stops this channel from playing.
+    pub fn stop(&self) {
+        Self::set_select(Self::select() & !ENABLE_BIT);
+    }
+}
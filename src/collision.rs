@@ -0,0 +1,515 @@
+use core::ops::{BitOr, BitOrAssign};
+
+use agb::fixnum::{Num, Rect, Vector2D};
+use bevy::prelude::*;
+
+/// Fixed-point number with 8 bits of fractional precision, used for positions and distances in
+/// collision queries.
+pub type Fixed = Num<i32, 8>;
+
+/// Per-tile collision flags stored in a [`CollisionLayer`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub struct TileFlags(u8);
+
+impl TileFlags {
+    /// The tile doesn't collide with anything.
+    pub const EMPTY: Self = Self(0);
+    /// The tile blocks movement from all directions.
+    pub const SOLID: Self = Self(1 << 0);
+    /// The tile only blocks movement from above, allowing objects to jump up through it.
+    pub const ONE_WAY: Self = Self(1 << 1);
+    /// The tile is a 45 degree slope rising towards the right, solid underneath its surface.
+    /// Combine with [`FLIPPED`](Self::FLIPPED) for a slope rising towards the left.
+    /// Takes precedence over [`SOLID`](Self::SOLID).
+    pub const SLOPE: Self = Self(1 << 2);
+    /// The tile is harmful to touch.
+    /// This has no effect on movement, but is reported by queries.
+    pub const HAZARD: Self = Self(1 << 3);
+    /// Mirrors a [`SLOPE`](Self::SLOPE) tile horizontally.
+    pub const FLIPPED: Self = Self(1 << 4);
+
+    /// Creates a set of flags from its raw bit representation.
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    /// Gets the raw bit representation of these flags.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Returns `true` if no flags are set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if all the flags in `other` are also set in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if any of the flags in `other` are also set in `self`.
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Returns the flags set in either `self` or `other`.
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl BitOr for TileFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl BitOrAssign for TileFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = self.union(rhs);
+    }
+}
+
+/// A grid of [`TileFlags`] describing how a background tilemap collides with objects.
+/// All positions are in pixels, with the top-left corner of the layer at the origin.
+///
+/// Tiles outside the layer are considered [empty](TileFlags::EMPTY) unless otherwise configured
+/// with [`with_out_of_bounds`](Self::with_out_of_bounds).
+#[derive(Resource, Clone, Debug)]
+pub struct CollisionLayer {
+    width: usize,
+    height: usize,
+    tile_size: i32,
+    out_of_bounds: TileFlags,
+    tiles: Vec<TileFlags>,
+}
+
+impl CollisionLayer {
+    /// Creates an empty [`CollisionLayer`] of `width` by `height` 8x8 pixel tiles.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            tile_size: 8,
+            out_of_bounds: TileFlags::EMPTY,
+            tiles: vec![TileFlags::EMPTY; width * height],
+        }
+    }
+
+    /// Creates a [`CollisionLayer`] from row-major tilemap data, using `flags` to determine the
+    /// collision flags of each tile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tiles` doesn't contain exactly `width * height` tiles.
+    pub fn from_tilemap<T: Copy>(
+        width: usize,
+        height: usize,
+        tiles: &[T],
+        mut flags: impl FnMut(T) -> TileFlags,
+    ) -> Self {
+        assert_eq!(
+            tiles.len(),
+            width * height,
+            "tilemap must contain exactly width * height tiles"
+        );
+
+        Self {
+            tiles: tiles.iter().map(|&tile| flags(tile)).collect(),
+            ..Self::new(width, height)
+        }
+    }
+
+    /// Sets the size of each tile in pixels.
+    /// Defaults to 8, the size of a hardware tile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tile_size` isn't positive.
+    pub fn with_tile_size(mut self, tile_size: i32) -> Self {
+        assert!(tile_size > 0, "tile size must be positive");
+        self.tile_size = tile_size;
+        self
+    }
+
+    /// Sets the flags reported for tiles outside this layer.
+    /// Use [`TileFlags::SOLID`] to keep objects from leaving the map.
+    pub const fn with_out_of_bounds(mut self, flags: TileFlags) -> Self {
+        self.out_of_bounds = flags;
+        self
+    }
+
+    /// The width of this layer in tiles.
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of this layer in tiles.
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The size of each tile in pixels.
+    pub const fn tile_size(&self) -> i32 {
+        self.tile_size
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let x = usize::try_from(x).ok().filter(|&x| x < self.width)?;
+        let y = usize::try_from(y).ok().filter(|&y| y < self.height)?;
+        Some(y * self.width + x)
+    }
+
+    /// Gets the flags of the tile at tile coordinates (`x`, `y`).
+    pub fn tile(&self, x: i32, y: i32) -> TileFlags {
+        self.index(x, y)
+            .map_or(self.out_of_bounds, |index| self.tiles[index])
+    }
+
+    /// Sets the flags of the tile at tile coordinates (`x`, `y`).
+    /// Does nothing if the coordinates are outside this layer.
+    pub fn set_tile(&mut self, x: i32, y: i32, flags: TileFlags) {
+        if let Some(index) = self.index(x, y) {
+            self.tiles[index] = flags;
+        }
+    }
+
+    /// Gets the tile coordinates containing the provided pixel position.
+    pub fn tile_coordinates(&self, position: Vector2D<Fixed>) -> Vector2D<i32> {
+        Vector2D::new(
+            position.x.floor().div_euclid(self.tile_size),
+            position.y.floor().div_euclid(self.tile_size),
+        )
+    }
+
+    /// Gets the flags of the tile containing the provided pixel position.
+    pub fn tile_at(&self, position: Vector2D<Fixed>) -> TileFlags {
+        let Vector2D { x, y } = self.tile_coordinates(position);
+        self.tile(x, y)
+    }
+
+    /// Returns the height of the surface of a slope tile at the horizontal pixel position `x`,
+    /// given the tile is at tile coordinates (`tile_x`, `tile_y`).
+    fn slope_surface(&self, flags: TileFlags, tile_x: i32, tile_y: i32, x: Fixed) -> Fixed {
+        let size = Fixed::new(self.tile_size);
+        let left = Fixed::new(tile_x * self.tile_size);
+        let top = Fixed::new(tile_y * self.tile_size);
+
+        let local = (x - left).max(Fixed::new(0)).min(size);
+
+        if flags.contains(TileFlags::FLIPPED) {
+            top + local
+        } else {
+            top + size - local
+        }
+    }
+
+    /// Returns `true` if the provided pixel position is inside a solid tile or underneath the
+    /// surface of a slope.
+    /// One-way tiles are never considered solid by this query.
+    pub fn is_solid(&self, position: Vector2D<Fixed>) -> bool {
+        let Vector2D { x, y } = self.tile_coordinates(position);
+        let flags = self.tile(x, y);
+
+        if flags.contains(TileFlags::SLOPE) {
+            return position.y >= self.slope_surface(flags, x, y, position.x);
+        }
+
+        flags.contains(TileFlags::SOLID)
+    }
+
+    /// Gets the range of tiles covered by `start..end` along one axis, where `end` is exclusive.
+    fn tile_span(&self, start: Fixed, end: Fixed) -> (i32, i32) {
+        let first = start.floor().div_euclid(self.tile_size);
+        let last = (end - Fixed::from_raw(1))
+            .floor()
+            .div_euclid(self.tile_size);
+        (first, last.max(first))
+    }
+
+    /// Returns the union of the flags of all tiles overlapped by `rect`.
+    pub fn overlapping(&self, rect: Rect<Fixed>) -> TileFlags {
+        let (left, right) = self.tile_span(rect.position.x, rect.position.x + rect.size.x);
+        let (top, bottom) = self.tile_span(rect.position.y, rect.position.y + rect.size.y);
+
+        let mut flags = TileFlags::EMPTY;
+
+        for y in top..=bottom {
+            for x in left..=right {
+                flags |= self.tile(x, y);
+            }
+        }
+
+        flags
+    }
+
+    /// Moves `rect` by `delta`, stopping it against solid tiles, one-way platforms (when moving
+    /// down onto them) and the surface of slopes.
+    /// Movement is resolved along the horizontal axis first, then the vertical axis.
+    pub fn sweep(&self, rect: Rect<Fixed>, delta: Vector2D<Fixed>) -> Sweep {
+        let zero = Fixed::new(0);
+        let mut sweep = Sweep {
+            rect,
+            hit_x: false,
+            hit_y: false,
+            grounded: false,
+            touched: TileFlags::EMPTY,
+        };
+
+        if delta.x != zero {
+            self.sweep_x(&mut sweep, delta.x);
+        }
+
+        if delta.y != zero {
+            self.sweep_y(&mut sweep, delta.y);
+        }
+
+        if delta.y >= zero {
+            self.snap_to_slope(&mut sweep);
+        }
+
+        sweep.touched = self.overlapping(sweep.rect);
+
+        sweep
+    }
+
+    fn sweep_x(&self, sweep: &mut Sweep, dx: Fixed) {
+        let rect = &mut sweep.rect;
+        let size = Fixed::new(self.tile_size);
+        let (top, bottom) = self.tile_span(rect.position.y, rect.position.y + rect.size.y);
+
+        let blocks = |column: i32| {
+            (top..=bottom).any(|row| {
+                let flags = self.tile(column, row);
+                flags.contains(TileFlags::SOLID) && !flags.contains(TileFlags::SLOPE)
+            })
+        };
+
+        if dx > Fixed::new(0) {
+            let edge = rect.position.x + rect.size.x;
+            let (start, _) = self.tile_span(edge, edge + Fixed::from_raw(1));
+            let (_, end) = self.tile_span(edge, edge + dx);
+
+            for column in start..=end {
+                if blocks(column) {
+                    let limit = Fixed::new(column) * size - rect.size.x;
+                    if limit < rect.position.x + dx {
+                        rect.position.x = limit.max(rect.position.x);
+                        sweep.hit_x = true;
+                        return;
+                    }
+                }
+            }
+        } else {
+            let edge = rect.position.x;
+            let (_, start) = self.tile_span(edge - Fixed::from_raw(1), edge);
+            let (end, _) = self.tile_span(edge + dx, edge);
+
+            for column in (end..=start).rev() {
+                if blocks(column) {
+                    let limit = Fixed::new(column + 1) * size;
+                    if limit > rect.position.x + dx {
+                        rect.position.x = limit.min(rect.position.x);
+                        sweep.hit_x = true;
+                        return;
+                    }
+                }
+            }
+        }
+
+        rect.position.x += dx;
+    }
+
+    fn sweep_y(&self, sweep: &mut Sweep, dy: Fixed) {
+        let rect = &mut sweep.rect;
+        let size = Fixed::new(self.tile_size);
+        let (left, right) = self.tile_span(rect.position.x, rect.position.x + rect.size.x);
+
+        if dy > Fixed::new(0) {
+            let edge = rect.position.y + rect.size.y;
+            let (start, _) = self.tile_span(edge, edge + Fixed::from_raw(1));
+            let (_, end) = self.tile_span(edge, edge + dy);
+
+            for row in start..=end {
+                let top = Fixed::new(row) * size;
+
+                let blocks = (left..=right).any(|column| {
+                    let flags = self.tile(column, row);
+                    !flags.contains(TileFlags::SLOPE)
+                        && (flags.contains(TileFlags::SOLID)
+                            || (flags.contains(TileFlags::ONE_WAY) && edge <= top))
+                });
+
+                if blocks && top < edge + dy {
+                    rect.position.y = top - rect.size.y;
+                    sweep.hit_y = true;
+                    sweep.grounded = true;
+                    return;
+                }
+            }
+        } else {
+            let edge = rect.position.y;
+            let (_, start) = self.tile_span(edge - Fixed::from_raw(1), edge);
+            let (end, _) = self.tile_span(edge + dy, edge);
+
+            for row in (end..=start).rev() {
+                let blocks = (left..=right).any(|column| {
+                    let flags = self.tile(column, row);
+                    flags.contains(TileFlags::SOLID) && !flags.contains(TileFlags::SLOPE)
+                });
+
+                let bottom = Fixed::new(row + 1) * size;
+
+                if blocks && bottom > edge + dy {
+                    rect.position.y = bottom.min(rect.position.y);
+                    sweep.hit_y = true;
+                    return;
+                }
+            }
+        }
+
+        rect.position.y += dy;
+    }
+
+    fn snap_to_slope(&self, sweep: &mut Sweep) {
+        let rect = &mut sweep.rect;
+        let foot = Vector2D::new(
+            rect.position.x + rect.size.x / 2,
+            rect.position.y + rect.size.y - Fixed::from_raw(1),
+        );
+
+        let Vector2D { x, y } = self.tile_coordinates(foot);
+
+        // Check the tile containing the foot, and the one above it in case the slope rises into
+        // the next tile.
+        for row in [y - 1, y] {
+            let flags = self.tile(x, row);
+
+            if !flags.contains(TileFlags::SLOPE) {
+                continue;
+            }
+
+            let surface = self.slope_surface(flags, x, row, foot.x);
+
+            if rect.position.y + rect.size.y > surface {
+                rect.position.y = surface - rect.size.y;
+                sweep.hit_y = true;
+                sweep.grounded = true;
+                return;
+            }
+        }
+    }
+
+    /// Casts a ray from `origin` along `direction`, stopping at the first tile which intersects
+    /// `mask`.
+    /// The ray travels at most the length of `direction`.
+    ///
+    /// Tiles are treated as full squares by this query, including slopes.
+    pub fn raycast(
+        &self,
+        origin: Vector2D<Fixed>,
+        direction: Vector2D<Fixed>,
+        mask: TileFlags,
+    ) -> Option<RayHit> {
+        let zero = Fixed::new(0);
+        let one = Fixed::new(1);
+        let size = Fixed::new(self.tile_size);
+
+        let mut tile = self.tile_coordinates(origin);
+        let target = self.tile_coordinates(origin + direction);
+
+        let flags = self.tile(tile.x, tile.y);
+        if flags.intersects(mask) {
+            return Some(RayHit {
+                position: origin,
+                tile,
+                flags,
+                fraction: zero,
+            });
+        }
+
+        let step = Vector2D::new(direction.x.to_raw().signum(), direction.y.to_raw().signum());
+
+        // Fraction of `direction` travelled when the next vertical or horizontal tile boundary is
+        // crossed.
+        let boundary = |axis_origin: Fixed, axis_direction: Fixed, axis_tile: i32, axis_step| {
+            if axis_direction == zero {
+                return None;
+            }
+
+            let edge = if axis_step > 0 {
+                Fixed::new(axis_tile + 1) * size
+            } else {
+                Fixed::new(axis_tile) * size
+            };
+
+            Some((edge - axis_origin) / axis_direction)
+        };
+
+        while tile != target {
+            let next_x = boundary(origin.x, direction.x, tile.x, step.x);
+            let next_y = boundary(origin.y, direction.y, tile.y, step.y);
+
+            let fraction = match (next_x, next_y) {
+                (Some(tx), next_y) if next_y.is_none_or(|ty| tx <= ty) => {
+                    tile.x += step.x;
+                    tx
+                }
+                (_, Some(ty)) => {
+                    tile.y += step.y;
+                    ty
+                }
+                (_, None) => return None,
+            };
+
+            if fraction > one {
+                return None;
+            }
+
+            let flags = self.tile(tile.x, tile.y);
+
+            if flags.intersects(mask) {
+                return Some(RayHit {
+                    position: origin + direction * fraction,
+                    tile,
+                    flags,
+                    fraction,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+/// The result of moving a rectangle through a [`CollisionLayer`] with
+/// [`sweep`](CollisionLayer::sweep).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Sweep {
+    /// The rectangle after movement has been resolved.
+    pub rect: Rect<Fixed>,
+    /// Whether horizontal movement was stopped by a tile.
+    pub hit_x: bool,
+    /// Whether vertical movement was stopped by a tile.
+    pub hit_y: bool,
+    /// Whether the rectangle is standing on a solid tile, one-way platform or slope.
+    pub grounded: bool,
+    /// The union of the flags of all tiles overlapped by the rectangle after movement.
+    pub touched: TileFlags,
+}
+
+/// The result of a successful [`raycast`](CollisionLayer::raycast).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RayHit {
+    /// The position where the ray entered the hit tile.
+    pub position: Vector2D<Fixed>,
+    /// The tile coordinates of the hit tile.
+    pub tile: Vector2D<i32>,
+    /// The flags of the hit tile.
+    pub flags: TileFlags,
+    /// How far along the ray the hit occurred, from 0 (at the origin) to 1 (at the end).
+    pub fraction: Fixed,
+}
@@ -0,0 +1,324 @@
+use core::mem;
+
+use agb::fixnum::{Rect, Vector2D};
+use bevy::{
+    platform_support::{collections::HashSet, sync::Arc},
+    prelude::*,
+    transform::TransformSystem,
+};
+
+use crate::Sprite;
+
+/// Detects overlaps between [`Hitbox`] and [`Hurtbox`] entities, sending a [`HitboxOverlap`] event
+/// when they start overlapping.
+///
+/// This plugin is not included in [`AgbPlugin`](crate::AgbPlugin) and must be added separately.
+#[derive(Default)]
+pub struct AgbHitboxPlugin;
+
+impl Plugin for AgbHitboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HitboxOverlap>().add_systems(
+            PostUpdate,
+            detect_hitbox_overlaps.after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// The slices contained within an aseprite file.
+///
+/// [`include_aseprite`](agb::include_aseprite) does not preserve slice data, so slices are instead
+/// read directly from the file:
+///
+/// ```ignore
+/// static HERO: &[u8] = include_bytes!("../assets/hero.aseprite");
+///
+/// let slices = AsepriteSlices::parse(HERO).unwrap();
+/// let hitbox = Hitbox(slices.get("punch").unwrap().clone());
+/// ```
+#[derive(Clone, Debug)]
+pub struct AsepriteSlices {
+    slices: Vec<Slice>,
+}
+
+impl AsepriteSlices {
+    /// Reads all slices from the contents of an aseprite file.
+    /// Returns [`None`] if `data` is not a valid aseprite file.
+    pub fn parse(data: &'static [u8]) -> Option<Self> {
+        const HEADER_MAGIC: u16 = 0xA5E0;
+        const FRAME_MAGIC: u16 = 0xF1FA;
+        const SLICE_CHUNK: u16 = 0x2022;
+
+        let mut reader = Reader(data);
+
+        let header = reader.take(128)?;
+        let mut header = Reader(header);
+
+        header.u32()?;
+        if header.u16()? != HEADER_MAGIC {
+            return None;
+        }
+
+        let frames = header.u16()?;
+        let canvas = Vector2D::new(i32::from(header.u16()?), i32::from(header.u16()?));
+
+        let mut slices = Vec::new();
+
+        for _ in 0..frames {
+            let size = reader.u32()? as usize;
+            let mut frame = Reader(reader.take(size.checked_sub(4)?)?);
+
+            if frame.u16()? != FRAME_MAGIC {
+                return None;
+            }
+
+            frame.take(10)?;
+
+            while !frame.0.is_empty() {
+                let size = frame.u32()? as usize;
+                let kind = frame.u16()?;
+                let chunk = frame.take(size.checked_sub(6)?)?;
+
+                if kind == SLICE_CHUNK {
+                    slices.push(Slice::parse(Reader(chunk), canvas)?);
+                }
+            }
+        }
+
+        Some(Self { slices })
+    }
+
+    /// Gets a slice by its name.
+    pub fn get(&self, name: &str) -> Option<&Slice> {
+        self.slices.iter().find(|slice| slice.name == name)
+    }
+
+    /// Iterates over all slices.
+    pub fn iter(&self) -> impl Iterator<Item = &Slice> {
+        self.slices.iter()
+    }
+}
+
+/// A named rectangle within an aseprite file, which may change between frames.
+#[derive(Clone, Debug)]
+pub struct Slice {
+    name: &'static str,
+    canvas: Vector2D<i32>,
+    keys: Arc<[SliceKey]>,
+}
+
+/// The rectangle of a [`Slice`] from a particular frame onwards.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SliceKey {
+    /// The first frame this key applies to.
+    pub frame: usize,
+    /// The rectangle covered by the slice, relative to the top-left corner of the sprite.
+    pub rect: Rect<i32>,
+}
+
+impl Slice {
+    fn parse(mut chunk: Reader, canvas: Vector2D<i32>) -> Option<Self> {
+        const NINE_PATCH: u32 = 1 << 0;
+        const PIVOT: u32 = 1 << 1;
+
+        /// Length of a key without its nine-patch or pivot data.
+        const KEY_SIZE: usize = 20;
+
+        let count = chunk.u32()?;
+        let flags = chunk.u32()?;
+        chunk.u32()?;
+
+        let length = chunk.u16()?;
+        let name = core::str::from_utf8(chunk.take(usize::from(length))?).ok()?;
+
+        // The count comes from the file, so it's only trusted as far as the data which follows.
+        let mut keys = Vec::with_capacity((count as usize).min(chunk.0.len() / KEY_SIZE));
+
+        for _ in 0..count {
+            let frame = chunk.u32()? as usize;
+            let x = chunk.u32()? as i32;
+            let y = chunk.u32()? as i32;
+            let width = chunk.u32()? as i32;
+            let height = chunk.u32()? as i32;
+
+            if flags & NINE_PATCH != 0 {
+                chunk.take(16)?;
+            }
+
+            if flags & PIVOT != 0 {
+                chunk.take(8)?;
+            }
+
+            keys.push(SliceKey {
+                frame,
+                rect: Rect::new(Vector2D::new(x, y), Vector2D::new(width, height)),
+            });
+        }
+
+        Some(Self {
+            name,
+            canvas,
+            keys: keys.into(),
+        })
+    }
+
+    /// Creates a [`Slice`] from keys authored by hand, for a sprite with the provided `canvas`
+    /// size.
+    /// `keys` must be sorted by frame.
+    pub fn new(name: &'static str, canvas: Vector2D<i32>, keys: &[SliceKey]) -> Self {
+        Self {
+            name,
+            canvas,
+            keys: keys.into(),
+        }
+    }
+
+    /// The name of this slice.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The keys describing this slice over time.
+    pub fn keys(&self) -> &[SliceKey] {
+        &self.keys
+    }
+
+    /// Gets the rectangle covered by this slice during `frame`, if any.
+    pub fn rect(&self, frame: usize) -> Option<Rect<i32>> {
+        let index = self.keys.partition_point(|key| key.frame <= frame);
+        let key = self.keys.get(index.checked_sub(1)?)?;
+
+        (key.rect.size.x > 0 && key.rect.size.y > 0).then_some(key.rect)
+    }
+
+    /// Gets the rectangle covered by this slice during `frame`, mirrored to match a sprite which
+    /// may be flipped.
+    pub fn oriented_rect(
+        &self,
+        frame: usize,
+        horizontal_flipped: bool,
+        vertical_flipped: bool,
+    ) -> Option<Rect<i32>> {
+        let mut rect = self.rect(frame)?;
+
+        if horizontal_flipped {
+            rect.position.x = self.canvas.x - rect.position.x - rect.size.x;
+        }
+
+        if vertical_flipped {
+            rect.position.y = self.canvas.y - rect.position.y - rect.size.y;
+        }
+
+        Some(rect)
+    }
+}
+
+struct Reader(&'static [u8]);
+
+impl Reader {
+    fn take(&mut self, count: usize) -> Option<&'static [u8]> {
+        let (taken, rest) = self.0.split_at_checked(count)?;
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+}
+
+/// The frame of an aseprite file currently displayed by an entity, used to select the active
+/// rectangle of its [`Hitbox`] and [`Hurtbox`].
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default, Debug, Deref, DerefMut)]
+pub struct AnimationFrame(pub usize);
+
+/// A region which deals hits to overlapping [`Hurtbox`] entities.
+#[derive(Component, Clone, Debug, Deref, DerefMut)]
+#[require(AnimationFrame, Transform)]
+pub struct Hitbox(pub Slice);
+
+/// A region which receives hits from overlapping [`Hitbox`] entities.
+#[derive(Component, Clone, Debug, Deref, DerefMut)]
+#[require(AnimationFrame, Transform)]
+pub struct Hurtbox(pub Slice);
+
+/// Sent when a [`Hitbox`] starts overlapping a [`Hurtbox`].
+/// Overlaps between components on the same entity are ignored.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HitboxOverlap {
+    /// The entity with the [`Hitbox`].
+    pub hitbox: Entity,
+    /// The entity with the [`Hurtbox`].
+    pub hurtbox: Entity,
+}
+
+fn world_rect(
+    slice: &Slice,
+    frame: &AnimationFrame,
+    transform: &GlobalTransform,
+    sprite: Option<&Sprite>,
+) -> Option<Rect<i32>> {
+    let (horizontal_flipped, vertical_flipped) = sprite.map_or((false, false), |sprite| {
+        (sprite.horizontal_flipped, sprite.vertical_flipped)
+    });
+
+    let mut rect = slice.oriented_rect(frame.0, horizontal_flipped, vertical_flipped)?;
+
+    let Vec3 { x, y, .. } = transform.translation();
+
+    rect.position += Vector2D::new(x as i32, y as i32);
+
+    Some(rect)
+}
+
+fn detect_hitbox_overlaps(
+    hitboxes: Query<(
+        Entity,
+        &Hitbox,
+        &AnimationFrame,
+        &GlobalTransform,
+        Option<&Sprite>,
+    )>,
+    hurtboxes: Query<(
+        Entity,
+        &Hurtbox,
+        &AnimationFrame,
+        &GlobalTransform,
+        Option<&Sprite>,
+    )>,
+    mut events: EventWriter<HitboxOverlap>,
+    mut previous: Local<HashSet<(Entity, Entity)>>,
+    mut current: Local<HashSet<(Entity, Entity)>>,
+) {
+    current.clear();
+
+    for (hitbox, slice, frame, transform, sprite) in &hitboxes {
+        let Some(attack) = world_rect(slice, frame, transform, sprite) else {
+            continue;
+        };
+
+        for (hurtbox, slice, frame, transform, sprite) in &hurtboxes {
+            if hitbox == hurtbox {
+                continue;
+            }
+
+            let Some(target) = world_rect(slice, frame, transform, sprite) else {
+                continue;
+            };
+
+            if attack.touches(target) {
+                current.insert((hitbox, hurtbox));
+            }
+        }
+    }
+
+    for &(hitbox, hurtbox) in current.difference(&previous) {
+        events.write(HitboxOverlap { hitbox, hurtbox });
+    }
+
+    mem::swap(&mut *previous, &mut *current);
+}
@@ -0,0 +1,318 @@
+use agb::fixnum::Vector2D;
+use bevy::{ecs::entity::hash_map::EntityHashMap, prelude::*};
+
+/// A node in a heads-up display, positioned relative to the edges of the screen or its parent
+/// [`HudNode`].
+///
+/// The [`AgbRenderPlugin`](crate::AgbRenderPlugin) resolves each node to a screen position
+/// whenever any node or the hierarchy changes, updating its [`Transform`] so
+/// [sprites](crate::Sprite) are drawn in place, and its [`ComputedHudNode`] so background tiles
+/// can be placed to match.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+#[require(Transform, ComputedHudNode)]
+pub struct HudNode {
+    /// The point within the parent (or the screen) this node is attached to.
+    /// Ignored when the parent [stacks](HudLayout) its children.
+    pub anchor: HudAnchor,
+    /// Distance in pixels from the anchored edges, pointing inwards.
+    pub margin: Vector2D<i32>,
+    /// Space in pixels between the edges of this node and its children.
+    pub padding: Vector2D<i32>,
+    /// Minimum size of this node in pixels.
+    /// Nodes which [stack](HudLayout) their children grow to fit them.
+    pub size: Vector2D<i32>,
+    /// How children of this node are arranged.
+    pub layout: HudLayout,
+}
+
+impl Default for HudNode {
+    fn default() -> Self {
+        Self::new(HudAnchor::TopLeft, Vector2D::new(0, 0))
+    }
+}
+
+impl HudNode {
+    /// Creates a [`HudNode`] of the provided size attached to `anchor`.
+    pub const fn new(anchor: HudAnchor, size: Vector2D<i32>) -> Self {
+        Self {
+            anchor,
+            margin: Vector2D::new(0, 0),
+            padding: Vector2D::new(0, 0),
+            size,
+            layout: HudLayout::Free,
+        }
+    }
+
+    /// Sets the [margin](Self::margin) of this node.
+    pub const fn with_margin(mut self, margin: Vector2D<i32>) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Sets the [padding](Self::padding) of this node.
+    pub const fn with_padding(mut self, padding: Vector2D<i32>) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets the [layout](Self::layout) of this node's children.
+    pub const fn with_layout(mut self, layout: HudLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+}
+
+/// The point a [`HudNode`] is attached to.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum HudAnchor {
+    /// The top-left corner.
+    #[default]
+    TopLeft,
+    /// The middle of the top edge.
+    Top,
+    /// The top-right corner.
+    TopRight,
+    /// The middle of the left edge.
+    Left,
+    /// The center.
+    Center,
+    /// The middle of the right edge.
+    Right,
+    /// The bottom-left corner.
+    BottomLeft,
+    /// The middle of the bottom edge.
+    Bottom,
+    /// The bottom-right corner.
+    BottomRight,
+}
+
+impl HudAnchor {
+    /// Position along each axis, where 0 is the start, 1 the middle, and 2 the end.
+    const fn alignment(self) -> (i32, i32) {
+        match self {
+            Self::TopLeft => (0, 0),
+            Self::Top => (1, 0),
+            Self::TopRight => (2, 0),
+            Self::Left => (0, 1),
+            Self::Center => (1, 1),
+            Self::Right => (2, 1),
+            Self::BottomLeft => (0, 2),
+            Self::Bottom => (1, 2),
+            Self::BottomRight => (2, 2),
+        }
+    }
+}
+
+/// How a [`HudNode`] arranges its children.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum HudLayout {
+    /// Each child is positioned independently using its own [anchor](HudNode::anchor).
+    #[default]
+    Free,
+    /// Children are placed left to right, separated by `spacing` pixels.
+    Horizontal {
+        /// Space in pixels between each child.
+        spacing: i32,
+    },
+    /// Children are placed top to bottom, separated by `spacing` pixels.
+    Vertical {
+        /// Space in pixels between each child.
+        spacing: i32,
+    },
+}
+
+/// The resolved screen position and size of a [`HudNode`].
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct ComputedHudNode {
+    /// Position of the top-left corner of the node on screen, in pixels.
+    pub position: Vector2D<i32>,
+    /// Size of the node in pixels.
+    pub size: Vector2D<i32>,
+}
+
+impl ComputedHudNode {
+    /// Position of the top-left corner of the node in 8x8 background tiles, rounded down.
+    pub fn tile_position(&self) -> Vector2D<i32> {
+        Vector2D::new(self.position.x.div_euclid(8), self.position.y.div_euclid(8))
+    }
+}
+
+type HudNodes<'w, 's> = Query<'w, 's, (&'static HudNode, Option<&'static Children>)>;
+
+type HudSizes = EntityHashMap<Vector2D<i32>>;
+
+/// Measures `entity` and every node beneath it, recording each size in `sizes`.
+fn measure(entity: Entity, nodes: &HudNodes, sizes: &mut HudSizes) -> Vector2D<i32> {
+    let Ok((node, children)) = nodes.get(entity) else {
+        return Vector2D::default();
+    };
+
+    let mut content = Vector2D::<i32>::default();
+    let mut count = 0;
+
+    for &child in children.into_iter().flatten() {
+        if !nodes.contains(child) {
+            continue;
+        }
+
+        let size = measure(child, nodes, sizes);
+
+        match node.layout {
+            HudLayout::Free => {}
+            HudLayout::Horizontal { .. } => {
+                content.x += size.x;
+                content.y = content.y.max(size.y);
+            }
+            HudLayout::Vertical { .. } => {
+                content.x = content.x.max(size.x);
+                content.y += size.y;
+            }
+        }
+
+        count += 1;
+    }
+
+    let size = match node.layout {
+        HudLayout::Free => node.size,
+        HudLayout::Horizontal { spacing } | HudLayout::Vertical { spacing } => {
+            let gaps = spacing * (count - 1).max(0);
+
+            if let HudLayout::Horizontal { .. } = node.layout {
+                content.x += gaps;
+            } else {
+                content.y += gaps;
+            }
+
+            let content = content + node.padding * 2;
+
+            Vector2D::new(node.size.x.max(content.x), node.size.y.max(content.y))
+        }
+    };
+
+    sizes.insert(entity, size);
+    size
+}
+
+fn arrange(
+    entity: Entity,
+    position: Vector2D<i32>,
+    parent_position: Vector2D<i32>,
+    nodes: &HudNodes,
+    sizes: &HudSizes,
+    computed: &mut Query<(&mut Transform, &mut ComputedHudNode)>,
+) {
+    let Ok((node, children)) = nodes.get(entity) else {
+        return;
+    };
+
+    let size = sizes.get(&entity).copied().unwrap_or_default();
+
+    if let Ok((mut transform, mut computed_node)) = computed.get_mut(entity) {
+        let local = position - parent_position;
+
+        let translation = Vec3::new(local.x as f32, local.y as f32, transform.translation.z);
+
+        if transform.translation != translation {
+            transform.translation = translation;
+        }
+
+        computed_node.set_if_neq(ComputedHudNode { position, size });
+    }
+
+    let content_position = position + node.padding;
+    let content_size = size - node.padding * 2;
+    let mut cursor = content_position;
+
+    for &child in children.into_iter().flatten() {
+        let Ok((child_node, _)) = nodes.get(child) else {
+            continue;
+        };
+
+        let child_size = sizes.get(&child).copied().unwrap_or_default();
+
+        match node.layout {
+            HudLayout::Free => {
+                let child_position =
+                    anchored(child_node, child_size, content_position, content_size);
+                arrange(child, child_position, position, nodes, sizes, computed);
+            }
+            HudLayout::Horizontal { spacing } => {
+                arrange(child, cursor, position, nodes, sizes, computed);
+                cursor.x += child_size.x + spacing;
+            }
+            HudLayout::Vertical { spacing } => {
+                arrange(child, cursor, position, nodes, sizes, computed);
+                cursor.y += child_size.y + spacing;
+            }
+        }
+    }
+}
+
+fn anchored(
+    node: &HudNode,
+    size: Vector2D<i32>,
+    container_position: Vector2D<i32>,
+    container_size: Vector2D<i32>,
+) -> Vector2D<i32> {
+    let align = |alignment: i32, container: i32, size: i32, margin: i32| match alignment {
+        0 => margin,
+        1 => (container - size) / 2 + margin,
+        _ => container - size - margin,
+    };
+
+    let (x, y) = node.anchor.alignment();
+
+    container_position
+        + Vector2D::new(
+            align(x, container_size.x, size.x, node.margin.x),
+            align(y, container_size.y, size.y, node.margin.y),
+        )
+}
+
+pub(crate) fn layout_hud(
+    changed: Query<
+        (),
+        (
+            With<HudNode>,
+            Or<(Changed<HudNode>, Changed<Children>, Changed<ChildOf>)>,
+        ),
+    >,
+    mut removed: RemovedComponents<HudNode>,
+    roots: Query<(Entity, Option<&ChildOf>), With<HudNode>>,
+    nodes: HudNodes,
+    mut computed: Query<(&mut Transform, &mut ComputedHudNode)>,
+    mut sizes: Local<HudSizes>,
+) {
+    // Layout is recomputed for the whole tree, so is skipped entirely while nothing changes.
+    let removed = removed.read().count() > 0;
+
+    if changed.is_empty() && !removed {
+        return;
+    }
+
+    let screen = Vector2D::new(agb::display::WIDTH, agb::display::HEIGHT);
+
+    sizes.clear();
+
+    for (entity, child_of) in &roots {
+        if child_of.is_some_and(|child_of| nodes.contains(child_of.parent)) {
+            continue;
+        }
+
+        let Ok((node, _)) = nodes.get(entity) else {
+            continue;
+        };
+
+        let size = measure(entity, &nodes, &mut sizes);
+        let position = anchored(node, size, Vector2D::default(), screen);
+
+        arrange(
+            entity,
+            position,
+            Vector2D::default(),
+            &nodes,
+            &sizes,
+            &mut computed,
+        );
+    }
+}
@@ -1,21 +1,68 @@
+use core::time::Duration;
+
+use agb::{
+    fixnum::{FixedWidthUnsignedInteger, Vector2D},
+    input::{Button, Tri},
+};
 use bevy::{
-    input::gamepad::{
-        GamepadAxisChangedEvent, GamepadButtonChangedEvent, GamepadButtonStateChangedEvent,
-        GamepadConnection, GamepadConnectionEvent, GamepadEvent, RawGamepadAxisChangedEvent,
-        RawGamepadButtonChangedEvent, RawGamepadEvent,
+    diagnostic::FrameCount,
+    input::{
+        InputPlugin, InputSystem,
+        gamepad::{
+            GamepadAxisChangedEvent, GamepadButtonChangedEvent, GamepadButtonStateChangedEvent,
+            GamepadConnection, GamepadConnectionEvent, GamepadEvent, GamepadRumbleRequest,
+            RawGamepadAxisChangedEvent, RawGamepadButtonChangedEvent, RawGamepadEvent,
+            gamepad_connection_system, gamepad_event_processing_system,
+        },
     },
     prelude::*,
 };
 
+mod action;
+mod combo;
+mod gesture;
+mod history;
+mod mapping;
+mod record;
+mod repeat;
+mod reset;
+mod stick;
+
+pub use action::*;
+pub use combo::*;
+pub use gesture::*;
+pub use history::*;
+pub use mapping::*;
+pub use record::*;
+pub use repeat::*;
+pub use reset::*;
+pub use stick::*;
+
 /// Makes the state of the GameBoy Advance's built in gamepad available using
-/// standard Bevy gamepad events.
-/// This plugin must be added alongside the [`InputPlugin`](bevy::input::InputPlugin).
+/// standard Bevy gamepad events, and the [`Gamepad`] component.
+///
+/// The gamepad parts of the [`InputPlugin`] are set up by this plugin, so the [`InputPlugin`]
+/// isn't required.
+/// Unlike the [`InputPlugin`], no types are registered for reflection and no keyboard, mouse, or
+/// touch resources are created, keeping memory usage low.
+/// If the [`InputPlugin`] is added anyway, its gamepad systems are used instead.
+///
+/// Which [`GamepadButton`] each button is reported as is set by the [`mapping`](Self::mapping).
+/// The D-pad also drives the left stick axes, as configured by the [`VirtualStick`] resource.
+/// Held buttons repeat according to the [`ButtonRepeat`] resource, and long presses and
+/// multi-taps are detected by [`ButtonGestures`].
+/// Recent input is kept in the [`InputHistory`], which is used to detect [`Combos`].
+/// Input can be recorded with the [`InputRecorder`] and replayed with [`InputPlayback`].
 #[derive(Default)]
-pub struct AgbInputPlugin;
+pub struct AgbInputPlugin {
+    /// The initial [`ButtonMapping`], which can be changed later through the resource.
+    pub mapping: ButtonMapping,
+}
 
 impl Plugin for AgbInputPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(ButtonController::new())
+            .insert_resource(self.mapping)
             .add_event::<GamepadEvent>()
             .add_event::<GamepadConnectionEvent>()
             .add_event::<GamepadButtonChangedEvent>()
@@ -24,10 +71,53 @@ impl Plugin for AgbInputPlugin {
             .add_event::<RawGamepadEvent>()
             .add_event::<RawGamepadAxisChangedEvent>()
             .add_event::<RawGamepadButtonChangedEvent>()
-            .add_systems(PreUpdate, update_gamepad);
+            .add_event::<GamepadRumbleRequest>()
+            .add_event::<ButtonRepeated>()
+            .add_event::<ComboCompleted>()
+            .add_event::<PlaybackFinished>()
+            .add_event::<RecordingFull>()
+            .add_event::<ButtonLongPressed>()
+            .add_event::<ButtonMultiTapped>()
+            .add_event::<ButtonHoldReleased>()
+            .init_resource::<VirtualStick>()
+            .init_resource::<ButtonRepeat>()
+            .init_resource::<InputHistory>()
+            .init_resource::<Combos>()
+            .init_resource::<InputRecorder>()
+            .init_resource::<InputPlayback>()
+            .init_resource::<ButtonGestures>()
+            .init_resource::<FrameCount>()
+            .add_systems(
+                PreUpdate,
+                (
+                    update_buttons,
+                    update_gamepad,
+                    (
+                        update_virtual_stick,
+                        update_button_repeat,
+                        update_button_gestures,
+                        detect_combos,
+                    ),
+                )
+                    .chain()
+                    .before(InputSystem),
+            )
+            .add_systems(Last, fix_replay_time);
     }
 
     fn finish(&self, app: &mut App) {
+        // Checked here rather than in `build` so the `InputPlugin` can be added in any order.
+        if !app.is_plugin_added::<InputPlugin>() {
+            app.add_systems(
+                PreUpdate,
+                (
+                    gamepad_connection_system,
+                    gamepad_event_processing_system.after(gamepad_connection_system),
+                )
+                    .in_set(InputSystem),
+            );
+        }
+
         let world = app.world_mut();
 
         let gamepad = world.spawn(GameBoyGamepad {}).id();
@@ -47,13 +137,158 @@ impl Plugin for AgbInputPlugin {
 }
 
 /// Helper to make it easy to get the current state of the GBA's buttons.
-#[derive(Resource, Deref, DerefMut)]
-pub struct ButtonController(agb::input::ButtonController);
+///
+/// Provides the same methods as [`agb::input::ButtonController`], but its state can also come
+/// from [`InputPlayback`] rather than the hardware.
+#[derive(Resource)]
+pub struct ButtonController {
+    hardware: agb::input::ButtonController,
+    previous: Button,
+    current: Button,
+}
 
 impl ButtonController {
     #[must_use]
     fn new() -> Self {
-        Self(agb::input::ButtonController::new())
+        let hardware = agb::input::ButtonController::new();
+        let current = Self::held_by(&hardware);
+
+        Self {
+            hardware,
+            previous: current,
+            current,
+        }
+    }
+
+    fn held_by(hardware: &agb::input::ButtonController) -> Button {
+        Button::all()
+            .iter()
+            .filter(|&button| hardware.is_pressed(button))
+            .collect()
+    }
+
+    /// The underlying [`agb`] controller, which always reflects the hardware buttons even during
+    /// [`InputPlayback`].
+    /// It's updated once per frame, before this controller.
+    #[must_use]
+    pub fn hardware(&self) -> &agb::input::ButtonController {
+        &self.hardware
+    }
+
+    /// Reads the buttons currently held on the hardware, without updating the controller.
+    pub(crate) fn read_hardware(&mut self) -> Button {
+        self.hardware.update();
+        Self::held_by(&self.hardware)
+    }
+
+    /// Advances to the next frame, where `held` are the buttons held.
+    pub(crate) fn update(&mut self, held: Button) {
+        self.previous = self.current;
+        self.current = held;
+    }
+
+    /// Every button currently held.
+    #[must_use]
+    pub fn held(&self) -> Button {
+        self.current
+    }
+
+    /// Every button which went from not pressed to pressed in the last frame.
+    #[must_use]
+    pub fn just_pressed(&self) -> Button {
+        self.current - self.previous
+    }
+
+    /// Every button which went from pressed to not pressed in the last frame.
+    #[must_use]
+    pub fn just_released(&self) -> Button {
+        self.previous - self.current
+    }
+
+    /// Returns [`Tri::Positive`] if right is pressed, [`Tri::Negative`] if left is pressed and
+    /// [`Tri::Zero`] if neither or both are pressed.
+    #[must_use]
+    pub fn x_tri(&self) -> Tri {
+        (
+            self.is_pressed(Button::LEFT),
+            self.is_pressed(Button::RIGHT),
+        )
+            .into()
+    }
+
+    /// Returns [`Tri::Positive`] if down is pressed, [`Tri::Negative`] if up is pressed and
+    /// [`Tri::Zero`] if neither or both are pressed.
+    #[must_use]
+    pub fn y_tri(&self) -> Tri {
+        (self.is_pressed(Button::UP), self.is_pressed(Button::DOWN)).into()
+    }
+
+    /// Returns a vector which represents the direction held on the D-pad.
+    #[must_use]
+    pub fn vector<T>(&self) -> Vector2D<T>
+    where
+        T: From<i32> + FixedWidthUnsignedInteger,
+    {
+        (self.x_tri() as i32, self.y_tri() as i32).into()
+    }
+
+    /// Like [`x_tri`](Self::x_tri), but only for buttons which were just pressed.
+    #[must_use]
+    pub fn just_pressed_x_tri(&self) -> Tri {
+        (
+            self.is_just_pressed(Button::LEFT),
+            self.is_just_pressed(Button::RIGHT),
+        )
+            .into()
+    }
+
+    /// Like [`y_tri`](Self::y_tri), but only for buttons which were just pressed.
+    #[must_use]
+    pub fn just_pressed_y_tri(&self) -> Tri {
+        (
+            self.is_just_pressed(Button::UP),
+            self.is_just_pressed(Button::DOWN),
+        )
+            .into()
+    }
+
+    /// Returns a vector which represents the direction the D-pad was just pressed in.
+    #[must_use]
+    pub fn just_pressed_vector<T>(&self) -> Vector2D<T>
+    where
+        T: From<i32> + FixedWidthUnsignedInteger,
+    {
+        (
+            self.just_pressed_x_tri() as i32,
+            self.just_pressed_y_tri() as i32,
+        )
+            .into()
+    }
+
+    /// Returns `true` if any of `keys` are pressed.
+    #[must_use]
+    pub fn is_pressed(&self, keys: Button) -> bool {
+        self.current.intersects(keys)
+    }
+
+    /// Returns `true` if none of `keys` are pressed. Equivalent to `!is_pressed(keys)`.
+    #[must_use]
+    pub fn is_released(&self, keys: Button) -> bool {
+        !self.is_pressed(keys)
+    }
+
+    /// Returns `true` if any of `keys` are pressed, and none of them were pressed on the
+    /// previous frame.
+    #[must_use]
+    pub fn is_just_pressed(&self, keys: Button) -> bool {
+        self.current.intersects(keys) && !self.previous.intersects(keys)
+    }
+
+    /// Returns `true` if none of `keys` are pressed, and any of them were pressed on the
+    /// previous frame.
+    #[must_use]
+    pub fn is_just_released(&self, keys: Button) -> bool {
+        !self.current.intersects(keys) && self.previous.intersects(keys)
     }
 }
 
@@ -63,19 +298,54 @@ impl ButtonController {
 #[non_exhaustive]
 pub struct GameBoyGamepad {}
 
-fn update_gamepad(
+pub(crate) fn update_buttons(
     mut manager: ResMut<ButtonController>,
+    mut recorder: ResMut<InputRecorder>,
+    mut playback: ResMut<InputPlayback>,
+    mut finished: EventWriter<PlaybackFinished>,
+    mut full: EventWriter<RecordingFull>,
+) {
+    let hardware = manager.read_hardware();
+
+    let held = match playback.advance(hardware) {
+        Ok(held) => held,
+        Err(event) => {
+            if let Some(event) = event {
+                finished.write(event);
+            }
+
+            hardware
+        }
+    };
+
+    manager.update(held);
+    if let Some(event) = recorder.record(held) {
+        full.write(event);
+    }
+}
+
+pub(crate) fn update_gamepad(
+    manager: Res<ButtonController>,
+    mapping: Res<ButtonMapping>,
     mut events: EventWriter<RawGamepadEvent>,
     mut button_events: EventWriter<RawGamepadButtonChangedEvent>,
+    mut history: ResMut<InputHistory>,
+    time: Option<Res<Time<Real>>>,
+    frame_count: Res<FrameCount>,
     gamepad: Single<Entity, With<GameBoyGamepad>>,
 ) {
-    manager.update();
-
     let gamepad = gamepad.into_inner();
 
-    agb::input::Button::all()
+    history.record(
+        frame_count.0,
+        manager.held(),
+        manager.just_pressed(),
+        manager.just_released(),
+        time.map_or(Duration::ZERO, |time| time.elapsed()),
+    );
+
+    mapping
         .iter()
-        .filter_map(agb_to_bevy_button)
         .filter_map(|(agb_button, bevy_button)| {
             manager
                 .is_just_pressed(agb_button)
@@ -88,23 +358,3 @@ fn update_gamepad(
             button_events.write(event);
         });
 }
-
-const fn agb_to_bevy_button(
-    button: agb::input::Button,
-) -> Option<(agb::input::Button, GamepadButton)> {
-    use agb::input::Button;
-
-    match button {
-        Button::A => Some((button, GamepadButton::East)),
-        Button::B => Some((button, GamepadButton::South)),
-        Button::SELECT => Some((button, GamepadButton::Select)),
-        Button::START => Some((button, GamepadButton::Start)),
-        Button::RIGHT => Some((button, GamepadButton::DPadRight)),
-        Button::LEFT => Some((button, GamepadButton::DPadLeft)),
-        Button::UP => Some((button, GamepadButton::DPadUp)),
-        Button::DOWN => Some((button, GamepadButton::DPadDown)),
-        Button::R => Some((button, GamepadButton::RightTrigger)),
-        Button::L => Some((button, GamepadButton::LeftTrigger)),
-        _ => None,
-    }
-}
@@ -0,0 +1,287 @@
+use alloc::vec::Vec;
+use core::{hash::Hash, marker::PhantomData};
+
+use agb::input::Button;
+use bevy::prelude::*;
+
+use super::{ButtonController, update_gamepad};
+use crate::SaveManager;
+
+/// A set of actions which can be bound to buttons with an [`InputMap`].
+///
+/// ```ignore
+/// #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+/// enum Action {
+///     Jump,
+///     Dash,
+/// }
+///
+/// impl Actionlike for Action {
+///     const ALL: &'static [Self] = &[Self::Jump, Self::Dash];
+/// }
+/// ```
+pub trait Actionlike: Copy + Eq + Hash + Send + Sync + 'static {
+    /// Every action, in a fixed order.
+    /// The position of each action is used to identify it in [saved](InputMap::save) bindings, so
+    /// new actions should be added to the end.
+    const ALL: &'static [Self];
+
+    /// Position of this action within [`ALL`](Self::ALL).
+    fn index(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|&action| action == self)
+            .unwrap_or(usize::MAX)
+    }
+}
+
+/// Maps each action to the buttons which trigger it.
+///
+/// Each binding is a set of [buttons](Button) which must all be held, so a single binding can
+/// be either one button or a chord such as `Button::L | Button::A`.
+/// An action is pressed while any of its bindings are held.
+#[derive(Resource, Clone, PartialEq, Eq, Debug)]
+pub struct InputMap<A: Actionlike> {
+    bindings: Vec<(A, Button)>,
+}
+
+impl<A: Actionlike> Default for InputMap<A> {
+    fn default() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+}
+
+impl<A: Actionlike> InputMap<A> {
+    /// Identifies saved bindings, followed by a version number.
+    const MAGIC: [u8; 4] = *b"BIND";
+    const VERSION: u8 = 1;
+
+    /// The most bindings which are [saved](Self::save).
+    pub const MAX_SAVED: usize = 256;
+
+    /// Each action is saved as a single byte, so there can be at most 255 of them.
+    const SAVABLE: () = assert!(
+        A::ALL.len() < u8::MAX as usize,
+        "at most 255 actions can be saved"
+    );
+
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a binding for `action`, returning the map.
+    pub fn with(mut self, action: A, buttons: Button) -> Self {
+        self.bind(action, buttons);
+        self
+    }
+
+    /// Adds a binding for `action`. Empty bindings are ignored.
+    pub fn bind(&mut self, action: A, buttons: Button) -> &mut Self {
+        if !buttons.is_empty() && !self.bindings.contains(&(action, buttons)) {
+            self.bindings.push((action, buttons));
+        }
+
+        self
+    }
+
+    /// Removes a binding from `action`.
+    pub fn unbind(&mut self, action: A, buttons: Button) -> &mut Self {
+        self.bindings
+            .retain(|&binding| binding != (action, buttons));
+        self
+    }
+
+    /// Removes every binding from `action`.
+    pub fn clear(&mut self, action: A) -> &mut Self {
+        self.bindings.retain(|&(bound, _)| bound != action);
+        self
+    }
+
+    /// Replaces every binding of `action` with `buttons`.
+    pub fn rebind(&mut self, action: A, buttons: Button) -> &mut Self {
+        self.clear(action).bind(action, buttons)
+    }
+
+    /// Iterates over the bindings of `action`.
+    pub fn bindings(&self, action: A) -> impl Iterator<Item = Button> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |&&(bound, _)| bound == action)
+            .map(|&(_, buttons)| buttons)
+    }
+
+    /// Iterates over every action and binding.
+    pub fn iter(&self) -> impl Iterator<Item = (A, Button)> + '_ {
+        self.bindings.iter().copied()
+    }
+
+    /// Returns `true` if `action` is held according to `buttons`.
+    pub fn is_pressed(&self, action: A, buttons: &ButtonController) -> bool {
+        self.bindings(action)
+            .any(|chord| chord.iter().all(|button| buttons.is_pressed(button)))
+    }
+
+    /// Encodes these bindings as bytes, suitable for [`from_bytes`](Self::from_bytes).
+    ///
+    /// Only the first [`MAX_SAVED`](Self::MAX_SAVED) bindings are encoded, and bindings of
+    /// actions missing from [`ALL`](Actionlike::ALL) are skipped.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let () = Self::SAVABLE;
+
+        let bindings = self
+            .bindings
+            .iter()
+            .filter_map(|&(action, buttons)| Some((u8::try_from(action.index()).ok()?, buttons)))
+            .take(Self::MAX_SAVED);
+
+        let mut bytes = Vec::with_capacity(
+            Self::MAGIC.len() + 3 + self.bindings.len().min(Self::MAX_SAVED) * 3,
+        );
+
+        bytes.extend_from_slice(&Self::MAGIC);
+        bytes.push(Self::VERSION);
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+
+        let mut count: u16 = 0;
+
+        for (index, buttons) in bindings {
+            bytes.push(index);
+            bytes.extend_from_slice(&(buttons.bits() as u16).to_le_bytes());
+            count += 1;
+        }
+
+        bytes[5..7].copy_from_slice(&count.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes bindings written by [`to_bytes`](Self::to_bytes).
+    /// Returns [`None`] if `bytes` don't hold valid bindings.
+    /// Bindings of actions which no longer exist are skipped.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (header, rest) = bytes.split_at_checked(Self::MAGIC.len() + 3)?;
+
+        if header[..4] != Self::MAGIC || header[4] != Self::VERSION {
+            return None;
+        }
+
+        let count = usize::from(u16::from_le_bytes([header[5], header[6]]));
+
+        if count > Self::MAX_SAVED {
+            return None;
+        }
+
+        let rest = rest.get(..count * 3)?;
+
+        let mut map = Self {
+            bindings: Vec::with_capacity(count),
+        };
+
+        for binding in rest.chunks_exact(3) {
+            let Some(&action) = A::ALL.get(usize::from(binding[0])) else {
+                continue;
+            };
+
+            let bits = u16::from_le_bytes([binding[1], binding[2]]);
+            let buttons = Button::from_bits_truncate(u32::from(bits));
+
+            map.bind(action, buttons);
+        }
+
+        Some(map)
+    }
+
+    /// Writes these bindings to save media at `offset`.
+    pub fn save(&self, save: &mut SaveManager, offset: usize) -> Result<(), agb::save::Error> {
+        let bytes = self.to_bytes();
+        let mut data = save.access()?;
+        let mut block = data.prepare_write(offset..offset + bytes.len())?;
+
+        block.write(offset, &bytes)
+    }
+
+    /// Reads bindings previously [saved](Self::save) at `offset`.
+    /// Returns [`None`] if no valid bindings were found, such as on the first boot.
+    pub fn load(save: &mut SaveManager, offset: usize) -> Result<Option<Self>, agb::save::Error> {
+        let mut data = save.access()?;
+
+        let mut header = [0; 7];
+        data.read(offset, &mut header)?;
+
+        if header[..4] != Self::MAGIC || header[4] != Self::VERSION {
+            return Ok(None);
+        }
+
+        let count = usize::from(u16::from_le_bytes([header[5], header[6]]));
+
+        let len = header.len() + count * 3;
+
+        // Avoids allocating for a count read from corrupt or unrelated save data.
+        if count > Self::MAX_SAVED || offset.checked_add(len).is_none_or(|end| end > data.len()) {
+            return Ok(None);
+        }
+
+        let mut bytes = alloc::vec![0; len];
+        data.read(offset, &mut bytes)?;
+
+        Ok(Self::from_bytes(&bytes))
+    }
+}
+
+/// The state of each action, updated from its [`InputMap`] every frame.
+///
+/// ```ignore
+/// fn jump(actions: Res<ActionState<Action>>) {
+///     if actions.just_pressed(Action::Jump) {
+///         // ...
+///     }
+/// }
+/// ```
+#[derive(Resource, Deref, DerefMut)]
+pub struct ActionState<A: Actionlike>(ButtonInput<A>);
+
+impl<A: Actionlike> Default for ActionState<A> {
+    fn default() -> Self {
+        Self(ButtonInput::default())
+    }
+}
+
+/// Maps the buttons of the Game Boy Advance to the actions `A`, using an [`InputMap`] and
+/// updating an [`ActionState`] every frame.
+pub struct AgbActionPlugin<A: Actionlike> {
+    _phantom: PhantomData<fn() -> A>,
+}
+
+impl<A: Actionlike> Default for AgbActionPlugin<A> {
+    fn default() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<A: Actionlike> Plugin for AgbActionPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap<A>>()
+            .init_resource::<ActionState<A>>()
+            .add_systems(PreUpdate, update_action_state::<A>.after(update_gamepad));
+    }
+}
+
+fn update_action_state<A: Actionlike>(
+    buttons: Res<ButtonController>,
+    map: Res<InputMap<A>>,
+    mut state: ResMut<ActionState<A>>,
+) {
+    state.clear();
+
+    for &action in A::ALL {
+        if map.is_pressed(action, &buttons) {
+            state.press(action);
+        } else {
+            state.release(action);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use alloc::vec::Vec;
+
+use agb::input::Button;
+use bevy::prelude::*;
+
+use super::{DPAD, InputHistory, InputRecord};
+
+/// One step of a [`Combo`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ComboStep {
+    /// The D-pad moves to exactly this direction, such as `Button::DOWN | Button::RIGHT`.
+    /// [`Button::empty`] matches returning the D-pad to neutral.
+    Direction(Button),
+    /// Every one of these buttons is held, with at least one of them just pressed.
+    Press(Button),
+}
+
+impl ComboStep {
+    fn matches(self, record: &InputRecord, mirrored: bool) -> bool {
+        match self {
+            Self::Direction(direction) => {
+                // Only the frame the direction was entered counts, not every change while it's
+                // held.
+                (record.pressed | record.released).intersects(DPAD)
+                    && record.direction() == mirror(direction, mirrored)
+            }
+            Self::Press(buttons) => {
+                let buttons = mirror(buttons, mirrored);
+
+                record.pressed.intersects(buttons) && record.held.contains(buttons)
+            }
+        }
+    }
+}
+
+/// Swaps left and right in `buttons` if `mirrored` is `true`.
+fn mirror(buttons: Button, mirrored: bool) -> Button {
+    if !mirrored {
+        return buttons;
+    }
+
+    let mut swapped = buttons - (Button::LEFT | Button::RIGHT);
+    swapped.set(Button::LEFT, buttons.contains(Button::RIGHT));
+    swapped.set(Button::RIGHT, buttons.contains(Button::LEFT));
+    swapped
+}
+
+/// A sequence of inputs which must be entered in order within a number of frames.
+///
+/// Combos are plain data, so they can be stored as `static` items:
+///
+/// ```ignore
+/// static FIREBALL: Combo = Combo::new(
+///     &[
+///         ComboStep::Direction(Button::DOWN),
+///         ComboStep::Direction(Button::DOWN.union(Button::RIGHT)),
+///         ComboStep::Direction(Button::RIGHT),
+///         ComboStep::Press(Button::A),
+///     ],
+///     20,
+/// )
+/// .mirrored();
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Combo {
+    /// The inputs, in the order they must be entered.
+    pub steps: &'static [ComboStep],
+    /// Maximum number of frames between the first and last step.
+    pub window: u32,
+    /// If `true`, the combo also matches with left and right swapped, such as when the player
+    /// faces the other way.
+    pub mirror: bool,
+}
+
+impl Combo {
+    /// Creates a combo which must be entered within `window` frames.
+    pub const fn new(steps: &'static [ComboStep], window: u32) -> Self {
+        Self {
+            steps,
+            window,
+            mirror: false,
+        }
+    }
+
+    /// Also matches this combo with left and right [swapped](Self::mirror).
+    pub const fn mirrored(mut self) -> Self {
+        self.mirror = true;
+        self
+    }
+
+    /// Returns `true` if this combo was completed on the latest frame of `history`.
+    fn completed(&self, history: &InputHistory, mirrored: bool) -> bool {
+        let Some((&last, earlier)) = self.steps.split_last() else {
+            return false;
+        };
+
+        let mut records = history
+            .iter()
+            .rev()
+            .take_while(|record| history.frame().wrapping_sub(record.frame) <= self.window);
+
+        let Some(latest) = records.next() else {
+            return false;
+        };
+
+        if latest.frame != history.frame() || !last.matches(latest, mirrored) {
+            return false;
+        }
+
+        earlier
+            .iter()
+            .rev()
+            .all(|step| records.any(|record| step.matches(record, mirrored)))
+    }
+}
+
+/// Identifies a [`Combo`] added to [`Combos`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ComboId(usize);
+
+/// The [combos](Combo) detected from the [`InputHistory`].
+/// A [`ComboCompleted`] event is sent each time one is entered.
+#[derive(Resource, Default)]
+pub struct Combos {
+    combos: Vec<Combo>,
+}
+
+impl Combos {
+    /// Starts detecting `combo`, returning an ID to identify it in [`ComboCompleted`] events.
+    pub fn add(&mut self, combo: Combo) -> ComboId {
+        self.combos.push(combo);
+        ComboId(self.combos.len() - 1)
+    }
+
+    /// The combo identified by `id`.
+    pub fn get(&self, id: ComboId) -> Option<&Combo> {
+        self.combos.get(id.0)
+    }
+}
+
+/// Sent when a [`Combo`] is completed.
+///
+/// If several combos are completed by the same input, such as a combo and a shorter combo it
+/// ends with, an event is sent for each.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ComboCompleted {
+    /// The combo which was completed.
+    pub combo: ComboId,
+    /// If `true`, the combo was entered with left and right [swapped](Combo::mirror).
+    pub mirrored: bool,
+}
+
+pub(crate) fn detect_combos(
+    combos: Res<Combos>,
+    history: Res<InputHistory>,
+    mut events: EventWriter<ComboCompleted>,
+) {
+    if history
+        .latest()
+        .is_none_or(|record| record.frame != history.frame())
+    {
+        return;
+    }
+
+    for (index, combo) in combos.combos.iter().enumerate() {
+        let mirrored = if combo.completed(&history, false) {
+            false
+        } else if combo.mirror && combo.completed(&history, true) {
+            true
+        } else {
+            continue;
+        };
+
+        events.write(ComboCompleted {
+            combo: ComboId(index),
+            mirrored,
+        });
+    }
+}
@@ -0,0 +1,158 @@
+use agb::input::Button;
+use bevy::{diagnostic::FrameCount, platform_support::collections::HashMap, prelude::*};
+
+use super::ButtonController;
+
+/// Tracks how long each button is held and how quickly it's tapped, for mechanics such as
+/// charge attacks and tap-to-dash.
+///
+/// Sends:
+/// * [`ButtonLongPressed`] once a button has been held for [`long_press`](Self::long_press).
+/// * [`ButtonMultiTapped`] when a button is pressed again within [`multi_tap`](Self::multi_tap)
+///   of its previous press.
+/// * [`ButtonHoldReleased`] when a button is released after a long press, with how long it was
+///   held.
+///
+/// Durations are counted in frames using the [`FrameCount`], so gestures are identical during
+/// [playback](super::InputPlayback).
+#[derive(Resource, Debug)]
+pub struct ButtonGestures {
+    /// Number of frames a button must be held for to count as a long press.
+    pub long_press: u32,
+    /// Maximum number of frames between presses for them to count as a multi-tap.
+    pub multi_tap: u32,
+    buttons: HashMap<Button, Gesture>,
+    frame: u32,
+}
+
+#[derive(Default, Debug)]
+struct Gesture {
+    pressed_at: Option<u32>,
+    last_press: Option<u32>,
+    taps: u32,
+    long_pressed: bool,
+}
+
+impl Default for ButtonGestures {
+    fn default() -> Self {
+        Self {
+            long_press: 30,
+            multi_tap: 15,
+            buttons: HashMap::default(),
+            frame: 0,
+        }
+    }
+}
+
+impl ButtonGestures {
+    /// Number of frames `button` has been held for, or 0 if it isn't held.
+    pub fn held_for(&self, button: Button) -> u32 {
+        self.buttons
+            .get(&button)
+            .and_then(|gesture| gesture.pressed_at)
+            .map_or(0, |pressed_at| self.frame.wrapping_sub(pressed_at))
+    }
+
+    /// Returns `true` if `button` is held and has been for at least
+    /// [`long_press`](Self::long_press).
+    pub fn is_long_pressed(&self, button: Button) -> bool {
+        self.buttons
+            .get(&button)
+            .is_some_and(|gesture| gesture.pressed_at.is_some() && gesture.long_pressed)
+    }
+
+    /// Number of times `button` has been tapped in quick succession, including the latest press.
+    /// A single press counts as 1, and a double-tap as 2.
+    pub fn taps(&self, button: Button) -> u32 {
+        self.buttons.get(&button).map_or(0, |gesture| gesture.taps)
+    }
+}
+
+/// Sent once a button has been held for [`ButtonGestures::long_press`].
+#[derive(Event, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ButtonLongPressed {
+    /// The button being held.
+    pub button: Button,
+}
+
+/// Sent when a button is pressed again within [`ButtonGestures::multi_tap`] of its previous
+/// press.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ButtonMultiTapped {
+    /// The button which was tapped.
+    pub button: Button,
+    /// Number of taps in quick succession, starting at 2 for a double-tap.
+    pub taps: u32,
+}
+
+/// Sent when a button is released after being held for at least [`ButtonGestures::long_press`],
+/// such as to release a charge attack.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ButtonHoldReleased {
+    /// The button which was released.
+    pub button: Button,
+    /// Number of frames the button was held for.
+    pub frames: u32,
+}
+
+pub(crate) fn update_button_gestures(
+    mut gestures: ResMut<ButtonGestures>,
+    buttons: Res<ButtonController>,
+    frame_count: Res<FrameCount>,
+    mut long_presses: EventWriter<ButtonLongPressed>,
+    mut multi_taps: EventWriter<ButtonMultiTapped>,
+    mut releases: EventWriter<ButtonHoldReleased>,
+) {
+    let gestures = &mut *gestures;
+    let now = frame_count.0;
+
+    gestures.frame = now;
+
+    for button in Button::all().iter() {
+        let gesture = gestures.buttons.entry(button).or_default();
+
+        if buttons.is_just_pressed(button) {
+            let quick = gesture
+                .last_press
+                .is_some_and(|last_press| now.wrapping_sub(last_press) <= gestures.multi_tap);
+
+            gesture.taps = if quick { gesture.taps + 1 } else { 1 };
+            gesture.last_press = Some(now);
+            gesture.pressed_at = Some(now);
+            gesture.long_pressed = false;
+
+            if gesture.taps > 1 {
+                multi_taps.write(ButtonMultiTapped {
+                    button,
+                    taps: gesture.taps,
+                });
+            }
+        }
+
+        let Some(pressed_at) = gesture.pressed_at else {
+            continue;
+        };
+
+        let frames = now.wrapping_sub(pressed_at);
+
+        if buttons.is_pressed(button) {
+            if !gesture.long_pressed && frames >= gestures.long_press {
+                gesture.long_pressed = true;
+                long_presses.write(ButtonLongPressed { button });
+            }
+
+            continue;
+        }
+
+        gesture.pressed_at = None;
+
+        if !gesture.long_pressed {
+            continue;
+        }
+
+        // A long press ends any run of taps.
+        gesture.last_press = None;
+
+        releases.write(ButtonHoldReleased { button, frames });
+    }
+}
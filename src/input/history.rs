@@ -0,0 +1,144 @@
+use alloc::collections::VecDeque;
+use core::time::Duration;
+
+use agb::input::Button;
+use bevy::prelude::*;
+
+/// A change in the state of the buttons, as recorded by [`InputHistory`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct InputRecord {
+    /// The [frame](InputHistory::frame) the change happened on.
+    pub frame: u32,
+    /// [`Time<Real>`] elapsed when the change happened, or [`Duration::ZERO`] if the
+    /// [`TimePlugin`](bevy::time::TimePlugin) isn't added.
+    pub time: Duration,
+    /// Every button held after the change.
+    pub held: Button,
+    /// Buttons which were pressed on this frame.
+    pub pressed: Button,
+    /// Buttons which were released on this frame.
+    pub released: Button,
+}
+
+impl InputRecord {
+    /// The direction held on the D-pad, or [`Button::empty`] if it's neutral.
+    pub fn direction(&self) -> Button {
+        self.held & DPAD
+    }
+}
+
+pub(crate) const DPAD: Button = Button::UP
+    .union(Button::DOWN)
+    .union(Button::LEFT)
+    .union(Button::RIGHT);
+
+/// Remembers recent changes to the buttons, for buffering inputs and detecting
+/// [combos](super::Combo).
+///
+/// A [record](InputRecord) is only added on frames where a button was pressed or released, so
+/// the history reaches further back than its [capacity](Self::capacity) in frames.
+///
+/// ```ignore
+/// fn jump(history: Res<InputHistory>, player: Single<&Grounded>) {
+///     // Allow jumping up to 4 frames before landing.
+///     if player.just_landed() && history.pressed_within(Button::A, 4) {
+///         // ...
+///     }
+/// }
+/// ```
+#[derive(Resource, Clone, Debug)]
+pub struct InputHistory {
+    records: VecDeque<InputRecord>,
+    capacity: usize,
+    frame: u32,
+}
+
+impl Default for InputHistory {
+    fn default() -> Self {
+        Self::with_capacity(32)
+    }
+}
+
+impl InputHistory {
+    /// Creates a history which remembers up to `capacity` changes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            frame: 0,
+        }
+    }
+
+    /// Maximum number of changes remembered.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of changes currently remembered.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns `true` if no changes are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The [`FrameCount`](bevy::diagnostic::FrameCount) when the buttons were last read, used
+    /// to identify each [record](InputRecord::frame).
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Iterates over the remembered changes, from oldest to newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &InputRecord> + ExactSizeIterator {
+        self.records.iter()
+    }
+
+    /// The most recent change, if any.
+    pub fn latest(&self) -> Option<&InputRecord> {
+        self.records.back()
+    }
+
+    /// Returns `true` if any of `buttons` were pressed within the last `frames` frames,
+    /// including the current frame.
+    pub fn pressed_within(&self, buttons: Button, frames: u32) -> bool {
+        self.records
+            .iter()
+            .rev()
+            .take_while(|record| self.frame.wrapping_sub(record.frame) < frames.max(1))
+            .any(|record| record.pressed.intersects(buttons))
+    }
+
+    /// Forgets every change, such as once a buffered input has been used.
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        frame: u32,
+        held: Button,
+        pressed: Button,
+        released: Button,
+        time: Duration,
+    ) {
+        self.frame = frame;
+
+        if pressed.is_empty() && released.is_empty() {
+            return;
+        }
+
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+        }
+
+        self.records.push_back(InputRecord {
+            frame: self.frame,
+            time,
+            held,
+            pressed,
+            released,
+        });
+    }
+}
@@ -0,0 +1,94 @@
+use agb::input::Button;
+use bevy::prelude::*;
+
+/// Number of buttons on the Game Boy Advance.
+const BUTTONS: usize = 10;
+
+/// Which [`GamepadButton`] each of the Game Boy Advance's buttons is reported as.
+///
+/// Desktop games often expect the bottom face button to confirm or jump, whereas on the
+/// Game Boy Advance that's usually A, the right face button.
+/// Choose a preset to suit how the game was written:
+/// * [`NINTENDO`](Self::NINTENDO) maps by position, so A is [`East`](GamepadButton::East) and B
+///   is [`South`](GamepadButton::South). This is the default.
+/// * [`XBOX`](Self::XBOX) maps by meaning, so A is [`South`](GamepadButton::South), like the A
+///   button of an Xbox controller, and B is [`East`](GamepadButton::East).
+///
+/// The mapping can be changed at any time through the resource, or set up front with
+/// [`AgbInputPlugin::mapping`](super::AgbInputPlugin::mapping).
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ButtonMapping {
+    buttons: [Option<GamepadButton>; BUTTONS],
+}
+
+impl Default for ButtonMapping {
+    fn default() -> Self {
+        Self::NINTENDO
+    }
+}
+
+impl ButtonMapping {
+    /// Maps each button by its position on the console.
+    pub const NINTENDO: Self = Self::EMPTY
+        .with(Button::A, GamepadButton::East)
+        .with(Button::B, GamepadButton::South)
+        .with_shared();
+
+    /// Maps each button by its meaning on an Xbox controller.
+    pub const XBOX: Self = Self::EMPTY
+        .with(Button::A, GamepadButton::South)
+        .with(Button::B, GamepadButton::East)
+        .with_shared();
+
+    /// A mapping where no button is reported.
+    pub const EMPTY: Self = Self {
+        buttons: [None; BUTTONS],
+    };
+
+    /// Maps the buttons which are the same in every preset.
+    const fn with_shared(self) -> Self {
+        self.with(Button::SELECT, GamepadButton::Select)
+            .with(Button::START, GamepadButton::Start)
+            .with(Button::RIGHT, GamepadButton::DPadRight)
+            .with(Button::LEFT, GamepadButton::DPadLeft)
+            .with(Button::UP, GamepadButton::DPadUp)
+            .with(Button::DOWN, GamepadButton::DPadDown)
+            .with(Button::R, GamepadButton::RightTrigger)
+            .with(Button::L, GamepadButton::LeftTrigger)
+    }
+
+    /// Position of `button` within the table. `button` must be a single button.
+    const fn index(button: Button) -> usize {
+        button.bits().trailing_zeros() as usize
+    }
+
+    /// Reports `button` as `gamepad_button`, returning the mapping.
+    pub const fn with(mut self, button: Button, gamepad_button: GamepadButton) -> Self {
+        if Self::index(button) < BUTTONS {
+            self.buttons[Self::index(button)] = Some(gamepad_button);
+        }
+
+        self
+    }
+
+    /// Reports `button` as `gamepad_button`, or stops reporting it if [`None`].
+    pub fn set(&mut self, button: Button, gamepad_button: Option<GamepadButton>) -> &mut Self {
+        if let Some(slot) = self.buttons.get_mut(Self::index(button)) {
+            *slot = gamepad_button;
+        }
+
+        self
+    }
+
+    /// The [`GamepadButton`] `button` is reported as, if any.
+    pub fn get(&self, button: Button) -> Option<GamepadButton> {
+        self.buttons.get(Self::index(button)).copied().flatten()
+    }
+
+    /// Iterates over every button which is reported, and what it's reported as.
+    pub fn iter(&self) -> impl Iterator<Item = (Button, GamepadButton)> + '_ {
+        Button::all()
+            .iter()
+            .filter_map(|button| Some((button, self.get(button)?)))
+    }
+}
@@ -0,0 +1,400 @@
+use alloc::{borrow::Cow, vec::Vec};
+
+use agb::input::Button;
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::SaveManager;
+
+/// Identifies an [`InputRecording`], followed by a version number.
+const MAGIC: [u8; 4] = *b"RPLY";
+const VERSION: u8 = 1;
+
+/// Length of the header: the magic, version, seed, and number of runs.
+const HEADER: usize = MAGIC.len() + 1 + 4 + 4;
+
+/// Length of each run: the buttons held and the number of frames they were held for.
+const RUN: usize = 4;
+
+/// The buttons held on each frame, recorded by an [`InputRecorder`] and played back by
+/// [`InputPlayback`].
+///
+/// Frames are stored as runs of identical input, so a recording of a few minutes of play is
+/// usually only a few kilobytes.
+/// Recordings can be written to save media with [`save`](Self::save), or embedded in the ROM
+/// and used directly with [`from_static`](Self::from_static):
+///
+/// ```ignore
+/// static DEMO: &[u8] = include_bytes!("../demo.rply");
+///
+/// let demo = InputRecording::from_static(DEMO).unwrap();
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InputRecording {
+    bytes: Cow<'static, [u8]>,
+}
+
+impl InputRecording {
+    fn new(seed: u32) -> Self {
+        let mut bytes = Vec::with_capacity(HEADER);
+
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&seed.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+
+        Self {
+            bytes: Cow::Owned(bytes),
+        }
+    }
+
+    /// Validates the header of `bytes`, returning the number of runs it holds.
+    fn validate(bytes: &[u8]) -> Option<usize> {
+        let header = bytes.get(..HEADER)?;
+
+        if header[..4] != MAGIC || header[4] != VERSION {
+            return None;
+        }
+
+        let runs = u32::from_le_bytes([header[9], header[10], header[11], header[12]]);
+        let runs = usize::try_from(runs).ok()?;
+
+        (Some(bytes.len()) == Self::encoded_len(runs)).then_some(runs)
+    }
+
+    /// Length of a recording holding `runs` runs, or [`None`] if it would overflow.
+    fn encoded_len(runs: usize) -> Option<usize> {
+        runs.checked_mul(RUN)?.checked_add(HEADER)
+    }
+
+    /// Uses a recording stored in the ROM, without copying it into RAM.
+    /// Returns [`None`] if `bytes` don't hold a valid recording.
+    pub fn from_static(bytes: &'static [u8]) -> Option<Self> {
+        Self::validate(bytes)?;
+
+        Some(Self {
+            bytes: Cow::Borrowed(bytes),
+        })
+    }
+
+    /// Decodes a recording written by [`as_bytes`](Self::as_bytes).
+    /// Returns [`None`] if `bytes` don't hold a valid recording.
+    pub fn from_bytes(bytes: Vec<u8>) -> Option<Self> {
+        Self::validate(&bytes)?;
+
+        Some(Self {
+            bytes: Cow::Owned(bytes),
+        })
+    }
+
+    /// The encoded recording, suitable for [`from_bytes`](Self::from_bytes) and
+    /// [`from_static`](Self::from_static).
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The seed [provided](InputRecorder::start) when recording started.
+    ///
+    /// Playback is only identical to the original play if the game is in the same state when
+    /// playback starts, so any random number generator should be reseeded with this value.
+    pub fn seed(&self) -> u32 {
+        u32::from_le_bytes([self.bytes[5], self.bytes[6], self.bytes[7], self.bytes[8]])
+    }
+
+    /// Number of runs of identical input.
+    fn runs(&self) -> usize {
+        (self.bytes.len() - HEADER) / RUN
+    }
+
+    /// The buttons held and number of frames they were held for in run `index`.
+    fn run(&self, index: usize) -> Option<(Button, u16)> {
+        let run = self
+            .bytes
+            .get(HEADER + index * RUN..HEADER + (index + 1) * RUN)?;
+        let buttons = u16::from_le_bytes([run[0], run[1]]);
+        let frames = u16::from_le_bytes([run[2], run[3]]);
+
+        Some((Button::from_bits_truncate(u32::from(buttons)), frames))
+    }
+
+    /// Number of frames recorded.
+    pub fn frames(&self) -> u32 {
+        (0..self.runs())
+            .filter_map(|index| self.run(index))
+            .map(|(_, frames)| u32::from(frames))
+            .sum()
+    }
+
+    fn push(&mut self, buttons: Button) {
+        let runs = self.runs();
+        let bytes = self.bytes.to_mut();
+
+        if let Some(last) = runs.checked_sub(1) {
+            let run = &mut bytes[HEADER + last * RUN..];
+            let held = u16::from_le_bytes([run[0], run[1]]);
+            let frames = u16::from_le_bytes([run[2], run[3]]);
+
+            if u32::from(held) == buttons.bits() && frames < u16::MAX {
+                run[2..4].copy_from_slice(&(frames + 1).to_le_bytes());
+                return;
+            }
+        }
+
+        bytes.extend_from_slice(&(buttons.bits() as u16).to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes[9..HEADER].copy_from_slice(&(runs as u32 + 1).to_le_bytes());
+    }
+
+    /// Writes this recording to save media at `offset`.
+    pub fn save(&self, save: &mut SaveManager, offset: usize) -> Result<(), agb::save::Error> {
+        let mut data = save.access()?;
+        let mut block = data.prepare_write(offset..offset + self.bytes.len())?;
+
+        block.write(offset, &self.bytes)
+    }
+
+    /// Reads a recording previously [saved](Self::save) at `offset`.
+    /// Returns [`None`] if no valid recording was found.
+    pub fn load(save: &mut SaveManager, offset: usize) -> Result<Option<Self>, agb::save::Error> {
+        let mut data = save.access()?;
+
+        let mut header = [0; HEADER];
+        data.read(offset, &mut header)?;
+
+        if header[..4] != MAGIC || header[4] != VERSION {
+            return Ok(None);
+        }
+
+        let runs = u32::from_le_bytes([header[9], header[10], header[11], header[12]]);
+
+        // A corrupt header could otherwise claim more runs than the save media can hold.
+        let Some(len) = usize::try_from(runs).ok().and_then(Self::encoded_len) else {
+            return Ok(None);
+        };
+
+        if offset.checked_add(len).is_none_or(|end| end > data.len()) {
+            return Ok(None);
+        }
+
+        let mut bytes = alloc::vec![0; len];
+        data.read(offset, &mut bytes)?;
+
+        Ok(Self::from_bytes(bytes))
+    }
+}
+
+/// Records the buttons held on each frame, as seen by the
+/// [`ButtonController`](super::ButtonController).
+///
+/// ```ignore
+/// fn start(mut recorder: ResMut<InputRecorder>, rng: Res<Rng>) {
+///     recorder.start(rng.seed());
+/// }
+///
+/// fn finish(mut recorder: ResMut<InputRecorder>, mut save: ResMut<SaveManager>) {
+///     if let Some(recording) = recorder.stop() {
+///         recording.save(&mut save, REPLAY_OFFSET).unwrap();
+///     }
+/// }
+/// ```
+///
+/// Each run of identical input takes 4 bytes, so recording stops once
+/// [`max_runs`](Self::max_runs) is reached, sending a [`RecordingFull`] event.
+#[derive(Resource)]
+pub struct InputRecorder {
+    /// The most runs of identical input a recording can hold.
+    pub max_runs: usize,
+    recording: Option<InputRecording>,
+    full: bool,
+}
+
+impl Default for InputRecorder {
+    fn default() -> Self {
+        Self {
+            max_runs: 4096,
+            recording: None,
+            full: false,
+        }
+    }
+}
+
+impl InputRecorder {
+    /// Starts a new recording from the next frame, discarding any recording in progress.
+    ///
+    /// `seed` is stored with the recording, and should be used to seed any random number
+    /// generator so that [playback](InputPlayback) matches the original play.
+    pub fn start(&mut self, seed: u32) {
+        self.recording = Some(InputRecording::new(seed));
+        self.full = false;
+    }
+
+    /// Stops recording, returning the recording if one was in progress or is
+    /// [full](RecordingFull).
+    pub fn stop(&mut self) -> Option<InputRecording> {
+        self.full = false;
+        self.recording.take()
+    }
+
+    /// Returns `true` if a recording is in progress.
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some() && !self.full
+    }
+
+    /// The recording in progress, if any.
+    pub fn recording(&self) -> Option<&InputRecording> {
+        self.recording.as_ref()
+    }
+
+    /// Records `buttons` as held for another frame, returning the event to send if the recording
+    /// just became full.
+    pub(crate) fn record(&mut self, buttons: Button) -> Option<RecordingFull> {
+        let recording = self.recording.as_mut().filter(|_| !self.full)?;
+
+        recording.push(buttons);
+
+        if recording.runs() < self.max_runs {
+            return None;
+        }
+
+        self.full = true;
+        Some(RecordingFull)
+    }
+}
+
+/// Plays back an [`InputRecording`] in place of the hardware buttons.
+///
+/// While playing, the [`ButtonController`](super::ButtonController) and every gamepad event
+/// reflect the recording rather than the buttons actually held.
+/// A [`PlaybackFinished`] event is sent once the recording ends or is
+/// [interrupted](Self::interruptible).
+///
+/// While recording or playing back, [`Time`] advances by exactly
+/// [`FRAME_DURATION`](crate::FRAME_DURATION) each frame rather than the real time elapsed, and
+/// any partial step of [`Time<Fixed>`] is discarded when either starts.
+/// This keeps [`FixedUpdate`] and anything else driven by [`Time`] identical between recording
+/// and playback.
+#[derive(Resource)]
+pub struct InputPlayback {
+    /// If `true`, pressing any button stops playback, such as to leave an attract-mode demo.
+    /// Buttons held when playback starts must be released and pressed again to interrupt it.
+    pub interruptible: bool,
+    recording: Option<InputRecording>,
+    run: usize,
+    frame: u16,
+    hardware: Button,
+}
+
+impl Default for InputPlayback {
+    fn default() -> Self {
+        Self {
+            interruptible: false,
+            recording: None,
+            run: 0,
+            frame: 0,
+            hardware: Button::empty(),
+        }
+    }
+}
+
+impl InputPlayback {
+    /// Starts playing `recording` from the next frame.
+    ///
+    /// The game should be in the same state as when recording started, including any random
+    /// number generator being seeded with the [seed](InputRecording::seed).
+    pub fn play(&mut self, recording: InputRecording) {
+        self.recording = Some(recording);
+        self.run = 0;
+        self.frame = 0;
+        // Otherwise, the press which started playback would also interrupt it.
+        self.hardware = Button::all();
+    }
+
+    /// Stops playback, returning to the hardware buttons.
+    pub fn stop(&mut self) -> Option<InputRecording> {
+        self.recording.take()
+    }
+
+    /// Returns `true` if a recording is playing.
+    pub fn is_playing(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// The recording playing, if any.
+    pub fn recording(&self) -> Option<&InputRecording> {
+        self.recording.as_ref()
+    }
+
+    /// Returns the buttons held on the next frame of the recording.
+    /// Otherwise, returns the event to send if playback just ended.
+    pub(crate) fn advance(&mut self, hardware: Button) -> Result<Button, Option<PlaybackFinished>> {
+        let Some(recording) = &self.recording else {
+            return Err(None);
+        };
+
+        let pressed = hardware - self.hardware;
+        self.hardware = hardware;
+
+        if self.interruptible && !pressed.is_empty() {
+            self.recording = None;
+            return Err(Some(PlaybackFinished { interrupted: true }));
+        }
+
+        loop {
+            let Some((buttons, frames)) = recording.run(self.run) else {
+                self.recording = None;
+                return Err(Some(PlaybackFinished { interrupted: false }));
+            };
+
+            if self.frame < frames {
+                self.frame += 1;
+                return Ok(buttons);
+            }
+
+            self.run += 1;
+            self.frame = 0;
+        }
+    }
+}
+
+/// Advances [`Time`] by exactly one frame per update while recording or playing back, so
+/// time-driven logic behaves the same way both times. The previous strategy is restored after.
+pub(crate) fn fix_replay_time(
+    recorder: Res<InputRecorder>,
+    playback: Res<InputPlayback>,
+    strategy: Option<ResMut<TimeUpdateStrategy>>,
+    fixed: Option<ResMut<Time<Fixed>>>,
+    mut previous: Local<Option<TimeUpdateStrategy>>,
+) {
+    let Some(mut strategy) = strategy else {
+        return;
+    };
+
+    let replaying = recorder.is_recording() || playback.is_playing();
+
+    if replaying && previous.is_none() {
+        let manual = TimeUpdateStrategy::ManualDuration(crate::FRAME_DURATION);
+        *previous = Some(core::mem::replace(&mut *strategy, manual));
+
+        if let Some(mut fixed) = fixed {
+            let overstep = fixed.overstep();
+            fixed.discard_overstep(overstep);
+        }
+    } else if !replaying {
+        if let Some(previous) = previous.take() {
+            *strategy = previous;
+        }
+    }
+}
+
+/// Sent when the [`InputRecorder`] reaches its [`max_runs`](InputRecorder::max_runs) and stops
+/// recording.
+/// The recording so far can still be retrieved with [`InputRecorder::stop`].
+#[derive(Event, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct RecordingFull;
+
+/// Sent when [`InputPlayback`] ends.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PlaybackFinished {
+    /// If `true`, playback was [interrupted](InputPlayback::interruptible) by a button press
+    /// rather than reaching the end of the recording.
+    pub interrupted: bool,
+}
@@ -0,0 +1,206 @@
+use core::time::Duration;
+
+use agb::input::Button;
+use bevy::{platform_support::collections::HashMap, prelude::*};
+
+use super::ButtonController;
+
+/// How often a held button repeats.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct RepeatTiming {
+    /// How long the button must be held before it first repeats.
+    pub delay: Duration,
+    /// Time between each repeat after the first.
+    pub rate: Duration,
+}
+
+impl RepeatTiming {
+    /// A timing suited to scrolling through menus.
+    pub const MENU: Self = Self::new(Duration::from_millis(400), Duration::from_millis(100));
+
+    /// Creates a timing with the provided delay and rate.
+    pub const fn new(delay: Duration, rate: Duration) -> Self {
+        Self { delay, rate }
+    }
+}
+
+impl Default for RepeatTiming {
+    fn default() -> Self {
+        Self::MENU
+    }
+}
+
+/// Repeats buttons while they're held, such as to scroll through a menu.
+///
+/// By default, the D-pad repeats with [`RepeatTiming::MENU`] and no other buttons repeat.
+/// Repeats are timed with [`Time<Real>`], so they continue while virtual time is paused.
+/// If [`frame_step`](Self::frame_step) is set, or the [`TimePlugin`](bevy::time::TimePlugin)
+/// isn't added, each frame instead counts as a fixed amount of time.
+///
+/// ```ignore
+/// fn scroll(repeat: Res<ButtonRepeat>, mut cursor: ResMut<Cursor>) {
+///     if repeat.is_triggered(Button::DOWN) {
+///         cursor.next();
+///     }
+/// }
+/// ```
+#[derive(Resource, Debug)]
+pub struct ButtonRepeat {
+    /// If set, each frame advances repeats by exactly this much rather than the real time
+    /// elapsed, such as [`FRAME_DURATION`](crate::FRAME_DURATION) to count frames.
+    pub frame_step: Option<Duration>,
+    timings: HashMap<Button, RepeatTiming>,
+    held: HashMap<Button, Held>,
+    triggered: Button,
+    repeated: Button,
+}
+
+#[derive(Debug)]
+struct Held {
+    elapsed: Duration,
+    next: Duration,
+    count: u32,
+}
+
+impl Held {
+    fn new(timing: &RepeatTiming) -> Self {
+        Self {
+            elapsed: Duration::ZERO,
+            next: timing.delay,
+            count: 0,
+        }
+    }
+}
+
+impl Default for ButtonRepeat {
+    fn default() -> Self {
+        let mut repeat = Self {
+            frame_step: None,
+            timings: HashMap::default(),
+            held: HashMap::default(),
+            triggered: Button::empty(),
+            repeated: Button::empty(),
+        };
+
+        repeat.enable(
+            Button::UP | Button::DOWN | Button::LEFT | Button::RIGHT,
+            RepeatTiming::MENU,
+        );
+
+        repeat
+    }
+}
+
+impl ButtonRepeat {
+    /// Makes each of `buttons` repeat with the provided timing.
+    pub fn enable(&mut self, buttons: Button, timing: RepeatTiming) -> &mut Self {
+        for button in buttons.iter() {
+            self.timings.insert(button, timing);
+        }
+
+        self
+    }
+
+    /// Stops each of `buttons` from repeating.
+    pub fn disable(&mut self, buttons: Button) -> &mut Self {
+        for button in buttons.iter() {
+            self.timings.remove(&button);
+            self.held.remove(&button);
+        }
+
+        self
+    }
+
+    /// The timing of `button`, or [`None`] if it doesn't repeat.
+    pub fn timing(&self, button: Button) -> Option<RepeatTiming> {
+        self.timings.get(&button).copied()
+    }
+
+    /// Returns `true` if any of `buttons` repeated this frame.
+    pub fn is_repeated(&self, buttons: Button) -> bool {
+        self.repeated.intersects(buttons)
+    }
+
+    /// Returns `true` if any of `buttons` were just pressed or repeated this frame.
+    pub fn is_triggered(&self, buttons: Button) -> bool {
+        self.triggered.intersects(buttons)
+    }
+
+    /// Number of times `button` has repeated since it was pressed, or 0 if it isn't held.
+    pub fn repeat_count(&self, button: Button) -> u32 {
+        self.held.get(&button).map_or(0, |held| held.count)
+    }
+}
+
+/// Sent each time a held button repeats, according to [`ButtonRepeat`].
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ButtonRepeated {
+    /// The button which repeated.
+    pub button: Button,
+    /// Number of times the button has repeated since it was pressed, starting at 1.
+    pub count: u32,
+}
+
+pub(crate) fn update_button_repeat(
+    mut repeat: ResMut<ButtonRepeat>,
+    buttons: Res<ButtonController>,
+    time: Option<Res<Time<Real>>>,
+    mut events: EventWriter<ButtonRepeated>,
+) {
+    let repeat = &mut *repeat;
+    let delta = match (repeat.frame_step, time) {
+        (Some(step), _) => step,
+        (None, Some(time)) => time.delta(),
+        (None, None) => crate::FRAME_DURATION,
+    };
+
+    repeat.triggered = Button::empty();
+    repeat.repeated = Button::empty();
+
+    for button in Button::all().iter() {
+        if buttons.is_just_pressed(button) {
+            repeat.triggered |= button;
+        }
+
+        let Some(timing) = repeat.timings.get(&button) else {
+            continue;
+        };
+
+        if !buttons.is_pressed(button) {
+            repeat.held.remove(&button);
+            continue;
+        }
+
+        if buttons.is_just_pressed(button) {
+            repeat.held.insert(button, Held::new(timing));
+            continue;
+        }
+
+        let held = repeat
+            .held
+            .entry(button)
+            .or_insert_with(|| Held::new(timing));
+
+        held.elapsed += delta;
+
+        if held.elapsed < held.next {
+            continue;
+        }
+
+        // Repeats missed during a long frame are skipped, rather than all firing at once.
+        held.next += timing.rate;
+        if held.next <= held.elapsed {
+            held.next = held.elapsed + timing.rate;
+        }
+
+        held.count += 1;
+
+        repeat.triggered |= button;
+        repeat.repeated |= button;
+
+        events.write(ButtonRepeated {
+            button,
+            count: held.count,
+        });
+    }
+}
@@ -0,0 +1,152 @@
+#![expect(
+    unsafe_code,
+    reason = "resetting the hardware requires calling the BIOS through inline assembly"
+)]
+
+use alloc::boxed::Box;
+use core::arch::asm;
+
+use agb::input::Button;
+use bevy::{prelude::*, state::state::FreelyMutableState};
+
+use super::{ButtonController, update_gamepad};
+
+const INTERRUPT_MASTER_ENABLE: *mut u16 = 0x0400_0208 as *mut u16;
+
+/// Read by the BIOS on a soft reset. If 0, execution restarts from the start of the ROM rather
+/// than RAM.
+const RESET_TO_RAM: *mut u8 = 0x0300_7FFA as *mut u8;
+
+/// Resets the game when a chord is pressed, by default A, B, START, and SELECT together, as is
+/// conventional for Game Boy Advance games.
+///
+/// This plugin isn't part of the [`AgbPlugin`](crate::AgbPlugin), so must be added to opt in.
+/// Whenever the chord is pressed a [`SoftReset`] event is sent, after which the game is either:
+/// * [restarted by the BIOS](Self::hardware), which is the default,
+/// * [returned to a state](Self::state), such as the title screen, or
+/// * [left to handle the event](Self::event) itself.
+pub struct AgbSoftResetPlugin {
+    /// The buttons which must be held together to reset.
+    pub chord: Button,
+    reset: Reset,
+}
+
+enum Reset {
+    Hardware,
+    Event,
+    State(Box<dyn Fn(&mut App) + Send + Sync>),
+}
+
+impl Default for AgbSoftResetPlugin {
+    fn default() -> Self {
+        Self::hardware()
+    }
+}
+
+impl AgbSoftResetPlugin {
+    /// The buttons which reset by default.
+    pub const CHORD: Button = Button::A
+        .union(Button::B)
+        .union(Button::START)
+        .union(Button::SELECT);
+
+    /// Restarts the game from the beginning using the BIOS, as with [`soft_reset`].
+    pub fn hardware() -> Self {
+        Self {
+            chord: Self::CHORD,
+            reset: Reset::Hardware,
+        }
+    }
+
+    /// Only sends the [`SoftReset`] event, leaving the game to handle it.
+    pub fn event() -> Self {
+        Self {
+            chord: Self::CHORD,
+            reset: Reset::Event,
+        }
+    }
+
+    /// Transitions to `state`, such as the title screen.
+    pub fn state<S: FreelyMutableState>(state: S) -> Self {
+        Self {
+            chord: Self::CHORD,
+            reset: Reset::State(Box::new(move |app| {
+                app.add_systems(
+                    PreUpdate,
+                    set_state(state.clone())
+                        .after(detect_soft_reset)
+                        .run_if(on_event::<SoftReset>),
+                );
+            })),
+        }
+    }
+
+    /// Resets when `chord` is pressed instead.
+    #[must_use]
+    pub fn with_chord(mut self, chord: Button) -> Self {
+        self.chord = chord;
+        self
+    }
+}
+
+impl Plugin for AgbSoftResetPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SoftResetChord(self.chord))
+            .add_event::<SoftReset>()
+            .add_systems(PreUpdate, detect_soft_reset.after(update_gamepad));
+
+        match &self.reset {
+            Reset::Hardware => {
+                app.add_systems(Last, reset_hardware.run_if(on_event::<SoftReset>));
+            }
+            Reset::Event => {}
+            Reset::State(add_systems) => add_systems(app),
+        }
+    }
+}
+
+/// Sent when the [soft reset](AgbSoftResetPlugin) chord is pressed.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SoftReset;
+
+#[derive(Resource)]
+struct SoftResetChord(Button);
+
+fn detect_soft_reset(
+    chord: Res<SoftResetChord>,
+    buttons: Res<ButtonController>,
+    mut events: EventWriter<SoftReset>,
+) {
+    let chord = chord.0;
+
+    // Only the frame the chord is completed counts, so holding it doesn't reset repeatedly.
+    if chord.iter().all(|button| buttons.is_pressed(button))
+        && chord.iter().any(|button| buttons.is_just_pressed(button))
+    {
+        events.write(SoftReset);
+    }
+}
+
+fn set_state<S: FreelyMutableState>(state: S) -> impl FnMut(ResMut<NextState<S>>) {
+    move |mut next| next.set(state.clone())
+}
+
+fn reset_hardware() {
+    soft_reset();
+}
+
+/// Restarts the game from the beginning using the BIOS `SoftReset` function.
+///
+/// Memory outside of the BIOS's reserved area isn't cleared, but is reinitialised as the game
+/// starts up again.
+pub fn soft_reset() -> ! {
+    // SAFETY: Disabling interrupts and writing the reset flag are always valid. The BIOS never
+    // returns from `SoftReset`, instead restarting from the start of the ROM.
+    unsafe {
+        INTERRUPT_MASTER_ENABLE.write_volatile(0);
+        RESET_TO_RAM.write_volatile(0);
+
+        // The function number is 0 in both ARM and Thumb modes.
+        asm!("swi 0x00", options(noreturn));
+    }
+}
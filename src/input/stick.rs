@@ -0,0 +1,131 @@
+use bevy::{
+    input::gamepad::{RawGamepadAxisChangedEvent, RawGamepadEvent},
+    prelude::*,
+};
+
+use super::{ButtonController, GameBoyGamepad};
+
+/// Drives the [`LeftStickX`](GamepadAxis::LeftStickX) and [`LeftStickY`](GamepadAxis::LeftStickY)
+/// axes of the [`GameBoyGamepad`] from the D-pad, so code written for analog sticks works
+/// unchanged.
+///
+/// The D-pad buttons are still reported as normal, so both can be used at once.
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct VirtualStick {
+    /// If `false`, no axis events are sent and the stick rests at the center.
+    pub enabled: bool,
+    /// Number of frames taken for an axis to reach full tilt after its direction is pressed.
+    /// If 0, the stick moves to full tilt immediately.
+    /// Releasing a direction always returns its axis to the center immediately.
+    pub ramp_frames: u32,
+    /// If `true`, diagonals are scaled to a length of 1, as on a round analog stick.
+    /// Otherwise, diagonals reach the corners of the square, with both axes at full tilt.
+    pub normalize_diagonals: bool,
+    tilt: Vec2,
+    value: Vec2,
+}
+
+impl Default for VirtualStick {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ramp_frames: 0,
+            normalize_diagonals: true,
+            tilt: Vec2::ZERO,
+            value: Vec2::ZERO,
+        }
+    }
+}
+
+impl VirtualStick {
+    /// Sets the number of [frames](Self::ramp_frames) taken to reach full tilt.
+    #[must_use]
+    pub fn with_ramp_frames(mut self, ramp_frames: u32) -> Self {
+        self.ramp_frames = ramp_frames;
+        self
+    }
+
+    /// Sets whether [diagonals are normalized](Self::normalize_diagonals).
+    #[must_use]
+    pub fn with_normalize_diagonals(mut self, normalize_diagonals: bool) -> Self {
+        self.normalize_diagonals = normalize_diagonals;
+        self
+    }
+
+    /// The position of the stick, as last reported through axis events.
+    /// Positive values point right and up.
+    pub fn value(&self) -> Vec2 {
+        self.value
+    }
+}
+
+/// Moves `tilt` one frame towards `target`, which is -1, 0, or 1.
+fn ramp(tilt: f32, target: f32, step: f32) -> f32 {
+    if target == 0. || tilt * target < 0. {
+        // Released, or reversed: start again from the center.
+        return if target == 0. { 0. } else { step * target };
+    }
+
+    (tilt + step * target).clamp(-1., 1.)
+}
+
+pub(crate) fn update_virtual_stick(
+    mut stick: ResMut<VirtualStick>,
+    buttons: Res<ButtonController>,
+    mut events: EventWriter<RawGamepadEvent>,
+    mut axis_events: EventWriter<RawGamepadAxisChangedEvent>,
+    gamepad: Single<Entity, With<GameBoyGamepad>>,
+) {
+    let target = if stick.enabled {
+        Vec2::new(
+            buttons.x_tri() as i32 as f32,
+            // The D-pad reports down as positive, whereas sticks report up as positive.
+            -(buttons.y_tri() as i32 as f32),
+        )
+    } else {
+        Vec2::ZERO
+    };
+
+    let step = if stick.ramp_frames == 0 {
+        1.
+    } else {
+        1. / stick.ramp_frames as f32
+    };
+
+    let tilt = Vec2::new(
+        ramp(stick.tilt.x, target.x, step),
+        ramp(stick.tilt.y, target.y, step),
+    );
+
+    let value = if stick.normalize_diagonals {
+        tilt.clamp_length_max(1.)
+    } else {
+        tilt
+    };
+
+    let previous = stick.value;
+
+    if stick.tilt != tilt {
+        stick.tilt = tilt;
+    }
+
+    if previous == value {
+        return;
+    }
+
+    stick.value = value;
+
+    let gamepad = gamepad.into_inner();
+
+    [
+        (GamepadAxis::LeftStickX, previous.x, value.x),
+        (GamepadAxis::LeftStickY, previous.y, value.y),
+    ]
+    .into_iter()
+    .filter(|&(_, previous, value)| previous != value)
+    .map(|(axis, _, value)| RawGamepadAxisChangedEvent::new(gamepad, axis, value))
+    .for_each(|event| {
+        events.write(event.into());
+        axis_events.write(event);
+    });
+}
@@ -5,23 +5,33 @@
 extern crate alloc;
 
 mod audio;
+mod collision;
 mod dma;
+mod hitbox;
+mod hud;
 mod input;
 mod logging;
+mod physics;
 mod render;
 mod runner;
 mod save;
+mod sleep;
 mod time;
 mod unpack;
 
 pub use agb;
 pub use audio::*;
+pub use collision::*;
 pub use dma::*;
+pub use hitbox::*;
+pub use hud::*;
 pub use input::*;
 pub use logging::*;
+pub use physics::*;
 pub use render::*;
 pub use runner::*;
 pub use save::*;
+pub use sleep::*;
 pub use time::*;
 pub use unpack::*;
 
@@ -37,8 +47,10 @@ plugin_group! {
         :AgbRunnerPlugin,
         :AgbTimePlugin,
         :AgbSavePlugin,
+        :AgbSleepPlugin,
         :AgbSoundPlugin,
         :AgbDmaPlugin,
+        bevy::diagnostic:::FrameCountPlugin,
     }
 }
 
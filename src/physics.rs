@@ -0,0 +1,363 @@
+use core::mem;
+
+use agb::fixnum::{Rect, Vector2D};
+use bevy::{platform_support::collections::HashSet, prelude::*};
+
+use crate::{CollisionLayer, Fixed, TileFlags};
+
+/// Provides simple arcade physics suitable for a few hundred entities:
+/// [gravity](Gravity), [velocity](Velocity), and axis-aligned [colliders](Collider) with
+/// broad-phase detection through a [`SpatialHash`].
+///
+/// All physics runs in [`FixedUpdate`], with velocities and accelerations measured in pixels per
+/// fixed timestep.
+/// If a [`CollisionLayer`] resource exists, entities with a [`Collider`] will be stopped by its
+/// solid tiles.
+///
+/// This plugin is not included in [`AgbPlugin`](crate::AgbPlugin) and must be added separately.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AgbPhysicsPlugin {
+    /// Size in pixels of each cell in the [`SpatialHash`].
+    /// Should be at least as large as most [colliders](Collider).
+    pub cell_size: i32,
+}
+
+impl Default for AgbPhysicsPlugin {
+    fn default() -> Self {
+        Self { cell_size: 32 }
+    }
+}
+
+impl Plugin for AgbPhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SpatialHash::new(self.cell_size))
+            .add_event::<CollisionEvent>()
+            .configure_sets(
+                FixedUpdate,
+                (
+                    PhysicsSystem::Integrate,
+                    PhysicsSystem::BroadPhase,
+                    PhysicsSystem::Collide,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    (apply_gravity, apply_velocity)
+                        .chain()
+                        .in_set(PhysicsSystem::Integrate),
+                    update_spatial_hash.in_set(PhysicsSystem::BroadPhase),
+                    detect_collisions.in_set(PhysicsSystem::Collide),
+                ),
+            );
+    }
+}
+
+/// System sets used by the [`AgbPhysicsPlugin`], run in order in [`FixedUpdate`].
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PhysicsSystem {
+    /// Applies [`Gravity`] and [`Velocity`], resolving movement against the [`CollisionLayer`].
+    Integrate,
+    /// Rebuilds the [`SpatialHash`] from the current position of all [colliders](Collider).
+    BroadPhase,
+    /// Sends [`CollisionEvent`]s for overlapping [colliders](Collider).
+    Collide,
+}
+
+/// The speed of an entity, in pixels per fixed timestep.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default, Debug, Deref, DerefMut)]
+#[require(Transform)]
+pub struct Velocity(pub Vector2D<Fixed>);
+
+/// A constant acceleration applied to an entity's [`Velocity`], in pixels per fixed timestep
+/// squared.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Deref, DerefMut)]
+#[require(Velocity)]
+pub struct Gravity(pub Vector2D<Fixed>);
+
+impl Default for Gravity {
+    fn default() -> Self {
+        Self(Vector2D::new(Fixed::new(0), Fixed::from_raw(1 << 6)))
+    }
+}
+
+/// An axis-aligned bounding box used for collision detection.
+/// The box is positioned relative to the entity's [`Transform`], which is the top-left corner of
+/// its [`Sprite`](crate::Sprite).
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+#[require(Transform)]
+pub struct Collider {
+    /// Offset of the top-left corner of the box from the entity's position.
+    pub offset: Vector2D<Fixed>,
+    /// Width and height of the box.
+    pub size: Vector2D<Fixed>,
+}
+
+impl Collider {
+    /// Creates a [`Collider`] of the provided size with no offset.
+    pub fn new(size: Vector2D<Fixed>) -> Self {
+        Self {
+            offset: Vector2D::default(),
+            size,
+        }
+    }
+
+    /// Sets the offset of this [`Collider`].
+    pub fn with_offset(mut self, offset: Vector2D<Fixed>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Gets the box covered by this [`Collider`] for an entity at the provided `translation`.
+    pub fn rect(&self, translation: Vec3) -> Rect<Fixed> {
+        Rect::new(to_fixed(translation) + self.offset, self.size)
+    }
+}
+
+/// Records how an entity with a [`Collider`] touched the [`CollisionLayer`] during its last
+/// movement.
+/// Add this alongside a [`Collider`] to have it updated by the [`AgbPhysicsPlugin`].
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct TileContacts {
+    /// Whether the entity is standing on the ground.
+    pub grounded: bool,
+    /// Whether horizontal movement was stopped by a wall.
+    pub hit_wall: bool,
+    /// Whether upwards movement was stopped by a ceiling.
+    pub hit_ceiling: bool,
+    /// The union of the flags of all tiles overlapped by the entity.
+    pub touched: TileFlags,
+}
+
+/// Sent when two entities with a [`Collider`] start or stop overlapping.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CollisionEvent {
+    /// The two entities started overlapping during this fixed timestep.
+    Started(Entity, Entity),
+    /// The two entities stopped overlapping during this fixed timestep.
+    Stopped(Entity, Entity),
+}
+
+/// Broad-phase acceleration structure which buckets [colliders](Collider) into a uniform grid of
+/// cells.
+/// Rebuilt every fixed timestep by the [`AgbPhysicsPlugin`], and can be used to find entities
+/// near a region with [`query`](Self::query).
+#[derive(Resource, Debug)]
+pub struct SpatialHash {
+    cell_size: i32,
+    entries: Vec<SpatialEntry>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct SpatialEntry {
+    cell: u32,
+    entity: Entity,
+    rect: Rect<Fixed>,
+}
+
+impl SpatialHash {
+    /// Creates an empty [`SpatialHash`] with cells `cell_size` pixels wide and tall.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_size` isn't positive.
+    pub fn new(cell_size: i32) -> Self {
+        assert!(cell_size > 0, "cell size must be positive");
+
+        Self {
+            cell_size,
+            entries: Vec::new(),
+        }
+    }
+
+    /// The size of each cell in pixels.
+    pub const fn cell_size(&self) -> i32 {
+        self.cell_size
+    }
+
+    const fn key(x: i32, y: i32) -> u32 {
+        ((x as u16 as u32) << 16) | (y as u16 as u32)
+    }
+
+    fn cell(&self, point: Vector2D<Fixed>) -> Vector2D<i32> {
+        Vector2D::new(
+            point.x.floor().div_euclid(self.cell_size),
+            point.y.floor().div_euclid(self.cell_size),
+        )
+    }
+
+    fn cells(&self, rect: Rect<Fixed>) -> impl Iterator<Item = Vector2D<i32>> + use<> {
+        let end = rect.position + rect.size - Vector2D::new(Fixed::from_raw(1), Fixed::from_raw(1));
+
+        let start = self.cell(rect.position);
+        let end = self.cell(end);
+
+        let end_x = end.x.max(start.x);
+        let end_y = end.y.max(start.y);
+
+        (start.y..=end_y).flat_map(move |y| (start.x..=end_x).map(move |x| Vector2D::new(x, y)))
+    }
+
+    fn bucket(&self, cell: Vector2D<i32>) -> &[SpatialEntry] {
+        let key = Self::key(cell.x, cell.y);
+        let start = self.entries.partition_point(|entry| entry.cell < key);
+        let end = self.entries.partition_point(|entry| entry.cell <= key);
+        &self.entries[start..end]
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Adds an entity covering `rect`.
+    /// Call [`sort`](Self::sort) once all entities have been added before querying.
+    pub fn insert(&mut self, entity: Entity, rect: Rect<Fixed>) {
+        for cell in self.cells(rect) {
+            self.entries.push(SpatialEntry {
+                cell: Self::key(cell.x, cell.y),
+                entity,
+                rect,
+            });
+        }
+    }
+
+    /// Prepares this [`SpatialHash`] for queries after entities have been [inserted](Self::insert).
+    pub fn sort(&mut self) {
+        self.entries.sort_unstable_by_key(|entry| entry.cell);
+    }
+
+    /// Iterates over all entities whose [`Collider`] overlaps `rect`.
+    /// Each entity is returned at most once.
+    pub fn query(&self, rect: Rect<Fixed>) -> impl Iterator<Item = Entity> + '_ {
+        self.cells(rect).flat_map(move |cell| {
+            self.bucket(cell).iter().filter_map(move |entry| {
+                let overlap = entry.rect.overlapping_rect(rect)?;
+
+                // Only report the overlap from the cell containing its top-left corner to avoid
+                // duplicates from entities spanning multiple cells.
+                (self.cell(overlap.position) == cell).then_some(entry.entity)
+            })
+        })
+    }
+
+    /// Iterates over all pairs of overlapping entities.
+    /// Each pair is returned at most once.
+    pub fn pairs(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.entries
+            .chunk_by(|a, b| a.cell == b.cell)
+            .flat_map(move |bucket| {
+                bucket.iter().enumerate().flat_map(move |(index, a)| {
+                    bucket[index + 1..].iter().filter_map(move |b| {
+                        if a.entity == b.entity {
+                            return None;
+                        }
+
+                        let overlap = a.rect.overlapping_rect(b.rect)?;
+
+                        let cell = self.cell(overlap.position);
+
+                        // As with `query`, only report each pair from a single cell.
+                        (Self::key(cell.x, cell.y) == a.cell)
+                            .then_some((a.entity.min(b.entity), a.entity.max(b.entity)))
+                    })
+                })
+            })
+    }
+}
+
+fn to_fixed(translation: Vec3) -> Vector2D<Fixed> {
+    Vector2D::new(
+        Fixed::from_f32(translation.x),
+        Fixed::from_f32(translation.y),
+    )
+}
+
+fn to_f32(value: Fixed) -> f32 {
+    value.to_raw() as f32 / (1 << 8) as f32
+}
+
+fn apply_gravity(mut entities: Query<(&mut Velocity, &Gravity)>) {
+    for (mut velocity, gravity) in &mut entities {
+        velocity.0 += gravity.0;
+    }
+}
+
+fn apply_velocity(
+    mut entities: Query<(
+        &mut Transform,
+        &mut Velocity,
+        Option<&Collider>,
+        Option<&mut TileContacts>,
+    )>,
+    layer: Option<Res<CollisionLayer>>,
+) {
+    let zero = Fixed::new(0);
+
+    for (mut transform, mut velocity, collider, contacts) in &mut entities {
+        let (Some(collider), Some(layer)) = (collider, layer.as_deref()) else {
+            transform.translation.x += to_f32(velocity.x);
+            transform.translation.y += to_f32(velocity.y);
+            continue;
+        };
+
+        let rect = collider.rect(transform.translation);
+        let sweep = layer.sweep(rect, velocity.0);
+
+        let moved = sweep.rect.position - rect.position;
+
+        transform.translation.x += to_f32(moved.x);
+        transform.translation.y += to_f32(moved.y);
+
+        if sweep.hit_x {
+            velocity.x = zero;
+        }
+
+        if sweep.hit_y {
+            velocity.y = zero;
+        }
+
+        if let Some(mut contacts) = contacts {
+            *contacts = TileContacts {
+                grounded: sweep.grounded,
+                hit_wall: sweep.hit_x,
+                hit_ceiling: sweep.hit_y && !sweep.grounded,
+                touched: sweep.touched,
+            };
+        }
+    }
+}
+
+fn update_spatial_hash(
+    mut spatial_hash: ResMut<SpatialHash>,
+    colliders: Query<(Entity, &Collider, &Transform)>,
+) {
+    spatial_hash.clear();
+
+    for (entity, collider, transform) in &colliders {
+        spatial_hash.insert(entity, collider.rect(transform.translation));
+    }
+
+    spatial_hash.sort();
+}
+
+fn detect_collisions(
+    spatial_hash: Res<SpatialHash>,
+    mut events: EventWriter<CollisionEvent>,
+    mut previous: Local<HashSet<(Entity, Entity)>>,
+    mut current: Local<HashSet<(Entity, Entity)>>,
+) {
+    current.clear();
+    current.extend(spatial_hash.pairs());
+
+    for &(a, b) in current.difference(&previous) {
+        events.write(CollisionEvent::Started(a, b));
+    }
+
+    for &(a, b) in previous.difference(&current) {
+        events.write(CollisionEvent::Stopped(a, b));
+    }
+
+    mem::swap(&mut *previous, &mut *current);
+}
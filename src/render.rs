@@ -1,11 +1,12 @@
 #![expect(
     unsafe_code,
-    reason = "sprite handle requires establishing safety invariants"
+    reason = "sprite handle and display registers require establishing safety invariants"
 )]
 
 use bevy::{
     platform_support::sync::{Arc, Weak},
     prelude::*,
+    transform::TransformSystem,
 };
 use log::warn;
 
@@ -15,7 +16,12 @@ pub struct AgbRenderPlugin;
 
 impl Plugin for AgbRenderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Last, render_objects);
+        app.init_resource::<DisplayLayers>()
+            .add_systems(
+                PostUpdate,
+                crate::hud::layout_hud.before(TransformSystem::TransformPropagate),
+            )
+            .add_systems(Last, (render_objects, apply_display_layers));
     }
 
     fn finish(&self, app: &mut App) {
@@ -180,3 +186,104 @@ pub struct WindowDist(agb::display::WindowDist);
 /// Provides access to [`Blend`](agb::display::blend::Blend).
 #[derive(Resource, Deref, DerefMut)]
 pub struct BlendDist(agb::display::BlendDist);
+
+/// Controls which layers are drawn by the display hardware, and the order in which backgrounds are
+/// drawn.
+/// The [`AgbRenderPlugin`] writes these settings to the display registers in [`Last`].
+/// Priority overrides are written every frame, as committing a background resets its priority,
+/// while the visibility of each layer is only written when these settings change.
+/// Backgrounds whose [visibility](BackgroundLayer::visible) is [`None`] are left as they are, so
+/// they can still be shown and hidden through [`Video`] directly.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DisplayLayers {
+    /// Settings for background layers 0 through 3.
+    pub backgrounds: [BackgroundLayer; 4],
+    /// Whether objects (sprites) are drawn.
+    pub objects: bool,
+    /// If `true`, the display will be forced blank (white), which also gives the CPU unrestricted
+    /// access to video memory. Useful while loading.
+    pub forced_blank: bool,
+}
+
+impl Default for DisplayLayers {
+    fn default() -> Self {
+        Self {
+            backgrounds: [BackgroundLayer::default(); 4],
+            objects: true,
+            forced_blank: false,
+        }
+    }
+}
+
+/// Settings for a single background layer within [`DisplayLayers`].
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BackgroundLayer {
+    /// If set, whether this background is drawn.
+    /// Otherwise, the visibility set when the background was last committed is used.
+    pub visible: Option<bool>,
+    /// If set, overrides the draw priority of this background.
+    /// Otherwise, the priority set when the background was last committed is used.
+    pub priority: Option<agb::display::Priority>,
+}
+
+const DISPLAY_CONTROL: *mut u16 = 0x0400_0000 as *mut u16;
+const BACKGROUND_CONTROL: *mut u16 = 0x0400_0008 as *mut u16;
+
+const FORCED_BLANK_BIT: u16 = 1 << 0x7;
+const FIRST_BACKGROUND_BIT: u16 = 0x8;
+const OBJECT_BIT: u16 = 1 << 0xC;
+
+fn apply_display_layers(layers: Res<DisplayLayers>) {
+    // Committing a background rewrites its whole control register, so priority overrides are
+    // applied every frame.
+    for (id, background) in layers.backgrounds.iter().enumerate() {
+        let Some(priority) = background.priority else {
+            continue;
+        };
+
+        // SAFETY: There are exactly 4 background control registers, laid out contiguously.
+        let register = unsafe { BACKGROUND_CONTROL.add(id) };
+
+        // SAFETY: Background control registers are always valid to read and write.
+        unsafe {
+            let control = register.read_volatile();
+            register.write_volatile((control & !0b11) | priority as u16);
+        }
+    }
+
+    if !layers.is_changed() {
+        return;
+    }
+
+    let mut mask = FORCED_BLANK_BIT | OBJECT_BIT;
+    let mut bits = 0;
+
+    if layers.forced_blank {
+        bits |= FORCED_BLANK_BIT;
+    }
+
+    if layers.objects {
+        bits |= OBJECT_BIT;
+    }
+
+    for (id, background) in layers.backgrounds.iter().enumerate() {
+        let Some(visible) = background.visible else {
+            continue;
+        };
+
+        let bit = 1 << (FIRST_BACKGROUND_BIT + id as u16);
+
+        mask |= bit;
+
+        if visible {
+            bits |= bit;
+        }
+    }
+
+    // SAFETY: The display control register is always valid to read and write.
+    // Only the layer enable and forced blank bits are modified.
+    unsafe {
+        let control = DISPLAY_CONTROL.read_volatile();
+        DISPLAY_CONTROL.write_volatile((control & !mask) | bits);
+    }
+}
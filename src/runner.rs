@@ -1,7 +1,11 @@
 use bevy::{app::PluginsState, prelude::*};
 
+use crate::Mixer;
+
 /// Sets up a [runner](App::set_runner) for the Bevy [application](App) which waits for VBlank
 /// between calls to [`update`](App::update).
+/// If the [`AgbSoundPlugin`](crate::AgbSoundPlugin) owns a [`Mixer`], it is serviced after each
+/// V-Blank.
 #[derive(Default)]
 pub struct AgbRunnerPlugin;
 
@@ -23,6 +27,10 @@ impl Plugin for AgbRunnerPlugin {
                 }
 
                 vblank.wait_for_vblank();
+
+                if let Some(mut mixer) = app.world_mut().get_non_send_resource_mut::<Mixer>() {
+                    mixer.frame();
+                }
             }
         });
     }
@@ -0,0 +1,170 @@
+#![expect(
+    unsafe_code,
+    reason = "sleeping requires direct access to the display, sound, and keypad registers"
+)]
+
+use agb::{
+    input::Button,
+    interrupt::{Interrupt, add_interrupt_handler},
+};
+use bevy::prelude::*;
+
+const DISPLAY_CONTROL: *mut u16 = 0x0400_0000 as *mut u16;
+const SOUND_CONTROL_DMG: *mut u16 = 0x0400_0080 as *mut u16;
+const SOUND_CONTROL_DIRECT: *mut u16 = 0x0400_0082 as *mut u16;
+const SOUND_CONTROL_MASTER: *mut u16 = 0x0400_0084 as *mut u16;
+const KEY_INPUT: *const u16 = 0x0400_0130 as *const u16;
+const KEY_CONTROL: *mut u16 = 0x0400_0132 as *mut u16;
+
+const FORCED_BLANK: u16 = 1 << 7;
+const KEY_IRQ_ENABLE: u16 = 1 << 14;
+const KEY_IRQ_ALL: u16 = 1 << 15;
+
+/// Lets the game put the Game Boy Advance to sleep with the [`Sleep`] resource.
+#[derive(Default)]
+pub struct AgbSleepPlugin;
+
+impl Plugin for AgbSleepPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Sleep>()
+            .add_event::<EnteringSleep>()
+            .add_event::<Woke>()
+            .add_systems(PreUpdate, update_sleep)
+            .add_systems(Last, enter_sleep);
+    }
+}
+
+/// Puts the Game Boy Advance to sleep, turning off the display and sound until the
+/// [wake](Self::wake) buttons are held together.
+///
+/// Once [requested](Self::request), an [`EnteringSleep`] event is sent at the start of the next
+/// frame, giving the game a chance to save or pause, and the system sleeps at the end of that
+/// frame.
+/// A [`Woke`] event is sent at the start of the frame after waking.
+///
+/// ```ignore
+/// fn sleep_on_chord(buttons: Res<ButtonController>, mut sleep: ResMut<Sleep>) {
+///     if buttons.is_just_pressed(Button::L) && buttons.is_pressed(Button::R | Button::SELECT) {
+///         sleep.request();
+///     }
+/// }
+/// ```
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Sleep {
+    /// The buttons which must all be held to wake up.
+    pub wake: Button,
+    requested: bool,
+    entering: bool,
+    woke: bool,
+}
+
+impl Default for Sleep {
+    fn default() -> Self {
+        Self {
+            wake: Self::WAKE,
+            requested: false,
+            entering: false,
+            woke: false,
+        }
+    }
+}
+
+impl Sleep {
+    /// The buttons which wake up by default, L, R, and SELECT.
+    pub const WAKE: Button = Button::L.union(Button::R).union(Button::SELECT);
+
+    /// Sleeps at the end of the next frame.
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// Returns `true` if sleep has been requested but hasn't started yet.
+    pub fn is_requested(&self) -> bool {
+        self.requested || self.entering
+    }
+}
+
+/// Sent at the start of the frame at the end of which the system [sleeps](Sleep).
+#[derive(Event, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct EnteringSleep;
+
+/// Sent at the start of the first frame after waking from [sleep](Sleep).
+#[derive(Event, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Woke;
+
+fn update_sleep(
+    mut sleep: ResMut<Sleep>,
+    mut entering: EventWriter<EnteringSleep>,
+    mut woke: EventWriter<Woke>,
+) {
+    if sleep.woke {
+        sleep.woke = false;
+        woke.write(Woke);
+    }
+
+    if sleep.requested {
+        sleep.requested = false;
+        sleep.entering = true;
+        entering.write(EnteringSleep);
+    }
+}
+
+fn enter_sleep(mut sleep: ResMut<Sleep>) {
+    if !sleep.entering {
+        return;
+    }
+
+    sleep.entering = false;
+    sleep_until(sleep.wake);
+    sleep.woke = true;
+}
+
+/// Buttons currently held, read directly from the hardware.
+fn held() -> Button {
+    // SAFETY: The key input register is always valid to read.
+    let keys = unsafe { KEY_INPUT.read_volatile() };
+
+    // Buttons read as 0 while held.
+    Button::from_bits_truncate(u32::from(!keys))
+}
+
+/// Immediately puts the Game Boy Advance to sleep until every one of `wake` is held, turning off
+/// the display and sound.
+///
+/// Prefer [`Sleep::request`], which gives the game a chance to prepare.
+pub fn sleep_until(wake: Button) {
+    // Waking relies on the buttons being pressed, so they must be released first.
+    while held().contains(wake) {}
+
+    // SAFETY: No allocation performed.
+    let interrupt = unsafe { add_interrupt_handler(Interrupt::Keypad, |_| {}) };
+
+    // SAFETY: The display, sound, and keypad registers are always valid to read and write.
+    // Every register changed is restored before returning.
+    unsafe {
+        let display = DISPLAY_CONTROL.read_volatile();
+        let dmg = SOUND_CONTROL_DMG.read_volatile();
+        let direct = SOUND_CONTROL_DIRECT.read_volatile();
+        let master = SOUND_CONTROL_MASTER.read_volatile();
+
+        DISPLAY_CONTROL.write_volatile(display | FORCED_BLANK);
+        SOUND_CONTROL_MASTER.write_volatile(0);
+        KEY_CONTROL.write_volatile(wake.bits() as u16 | KEY_IRQ_ENABLE | KEY_IRQ_ALL);
+
+        agb::syscall::stop();
+
+        KEY_CONTROL.write_volatile(0);
+
+        // Disabling the master sound clears the other sound registers, so they're restored
+        // once it's enabled again.
+        SOUND_CONTROL_MASTER.write_volatile(master);
+        SOUND_CONTROL_DMG.write_volatile(dmg);
+        SOUND_CONTROL_DIRECT.write_volatile(direct);
+        DISPLAY_CONTROL.write_volatile(display);
+    }
+
+    drop(interrupt);
+
+    // Otherwise, the buttons used to wake would also be seen as input by the game.
+    while held().intersects(wake) {}
+}
@@ -5,6 +5,10 @@ use bevy::{
     prelude::*,
 };
 
+/// Time taken for the Game Boy Advance to draw a single frame: 280,896 cycles of its 16.78MHz
+/// clock, or roughly 59.73 frames per second.
+pub const FRAME_DURATION: Duration = Duration::from_nanos(16_742_706);
+
 /// Sets up [timers](Timer) 2 & 3 as [resources](Resource).
 /// Uses [`Timer 2`](Timer) to provide [`Instant`](bevy::platform_support::time::Instant)
 /// with reliable timing information.